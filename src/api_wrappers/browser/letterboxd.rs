@@ -1,5 +1,5 @@
 use crate::api_wrappers::browser::{APIWrapper, BrowserAPIWrapper, implementation_utils};
-use crate::environment::environment;
+use crate::environment::types::LetterboxdAccount;
 use crate::utils::constants::APIWrapperIdentificator;
 use crate::{impl_browser_api_wrapper, init_new_browser_api_wrapper};
 use anyhow::{Context, Result};
@@ -49,8 +49,8 @@ impl LetterboxdBrowserAPIWrapper {
         Ok(bytes.to_vec())
     }
 
-    pub async fn launch(&self) -> Result<()> {
-        self.login().await?;
+    pub async fn launch(&self, account: &LetterboxdAccount) -> Result<()> {
+        self.login(account).await?;
         Ok(())
     }
 
@@ -61,19 +61,17 @@ impl LetterboxdBrowserAPIWrapper {
             .expect("Failed to close the browser!");
     }
 
-    async fn login(&self) -> Result<()> {
+    async fn login(&self, account: &LetterboxdAccount) -> Result<()> {
         self.client
             .goto("https://letterboxd.com/sign-in")
             .await
             .context("Navigation to `letterboxd.com/sign-in` failed!")?;
 
-        let config = environment();
-
         self.client
             .find(Locator::Id("field-username"))
             .await
             .context("Failed to get `field-username` input!")?
-            .send_keys(&config.letterboxd.username)
+            .send_keys(&account.username)
             .await
             .context("Failed to insert keys into `field-username` input!")?;
 
@@ -81,7 +79,7 @@ impl LetterboxdBrowserAPIWrapper {
             .find(Locator::Id("field-password"))
             .await
             .context("Failed to get `field-password` input!")?
-            .send_keys(&config.letterboxd.password)
+            .send_keys(&account.password)
             .await
             .context("Failed to insert keys into `field-password` input!")?;
 
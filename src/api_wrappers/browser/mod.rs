@@ -4,11 +4,47 @@ use crate::logger::logger;
 use async_trait::async_trait;
 use fantoccini::Client;
 use fantoccini::ClientBuilder;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time::sleep;
 
 pub mod letterboxd;
 
+tokio::task_local! {
+    static OPEN_CLIENTS: Arc<Mutex<Vec<Client>>>;
+}
+
+// Keeps track of browser clients connected while running a future, so that their sessions can be
+// closed if the future gets cancelled before closing them itself.
+#[derive(Clone, Default)]
+pub struct BrowserSessions {
+    clients: Arc<Mutex<Vec<Client>>>,
+}
+
+impl BrowserSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn track<F: Future>(&self, future: F) -> F::Output {
+        OPEN_CLIENTS.scope(self.clients.clone(), future).await
+    }
+
+    // Returns the amount of sessions that have been closed. Sessions closed before are skipped.
+    pub async fn close_all(&self) -> usize {
+        let clients: Vec<Client> = self.clients.lock().unwrap().drain(..).collect();
+
+        let mut amount_of_closed_sessions = 0;
+        for client in clients {
+            if client.close().await.is_ok() {
+                amount_of_closed_sessions += 1;
+            }
+        }
+        amount_of_closed_sessions
+    }
+}
+
 #[async_trait]
 pub trait BrowserAPIWrapper: APIWrapper {
     fn from_client(client: Client) -> Self;
@@ -26,7 +62,11 @@ async fn get_client() -> Client {
             .await;
 
         match client {
-            Ok(c) => return c,
+            Ok(c) => {
+                // Clients connected outside of `BrowserSessions::track` aren't tracked.
+                let _ = OPEN_CLIENTS.try_with(|clients| clients.lock().unwrap().push(c.clone()));
+                return c;
+            }
             Err(_) => {
                 logger().error(
                     "Failed to establish a connection to WebDriver (:4444)! Make sure that it's running. If it already is, this error has probably appeared because another client is already connected.",
@@ -0,0 +1,183 @@
+use crate::utils::time::system_time_to_str;
+use anyhow::{Context, Result, bail};
+use rusqlite::Connection;
+use std::time::SystemTime;
+
+pub struct Migration {
+    pub version: u64,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+// All changes ever made to the database schema, oldest first. Already released migrations must
+// never be modified, add a new one instead. Tables created before versioning was introduced use
+// `IF NOT EXISTS`, so that databases created back then can be upgraded too.
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Create the schedule table",
+        sql: "CREATE TABLE IF NOT EXISTS schedule (
+            id          INTEGER PRIMARY KEY,
+            app_name    TEXT NOT NULL,
+            next_run    TEXT NOT NULL
+        );",
+    },
+    Migration {
+        version: 2,
+        description: "Create tables used by request archivers",
+        sql: "CREATE TABLE IF NOT EXISTS pending_requests (
+            id              INTEGER PRIMARY KEY,
+            app_name        TEXT NOT NULL,
+            requested_at    TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS handled_mails (
+            id          INTEGER PRIMARY KEY,
+            app_name    TEXT NOT NULL,
+            message_id  TEXT NOT NULL,
+            handled_at  TEXT NOT NULL
+        );",
+    },
+    Migration {
+        version: 3,
+        description: "Create the backups table",
+        sql: "CREATE TABLE IF NOT EXISTS backups (
+            id              INTEGER PRIMARY KEY,
+            app_name        TEXT NOT NULL,
+            path            TEXT NOT NULL,
+            content_hash    TEXT NOT NULL,
+            size            INTEGER NOT NULL,
+            status          TEXT NOT NULL,
+            created_at      TEXT NOT NULL
+        );",
+    },
+    Migration {
+        version: 4,
+        description: "Create the runs table",
+        sql: "CREATE TABLE IF NOT EXISTS runs (
+            id              INTEGER PRIMARY KEY,
+            app_name        TEXT NOT NULL,
+            started_at      TEXT NOT NULL,
+            finished_at     TEXT,
+            outcome         TEXT NOT NULL,
+            error_message   TEXT,
+            bytes_uploaded  INTEGER,
+            storage_path    TEXT
+        );",
+    },
+    Migration {
+        version: 5,
+        description: "Create the run_requests table",
+        sql: "CREATE TABLE run_requests (
+            id              INTEGER PRIMARY KEY,
+            app_name        TEXT NOT NULL,
+            reschedule      INTEGER NOT NULL,
+            requested_at    TEXT NOT NULL
+        );",
+    },
+    Migration {
+        version: 6,
+        description: "Create the paused_tasks table",
+        sql: "CREATE TABLE paused_tasks (
+            app_name    TEXT PRIMARY KEY,
+            paused_at   TEXT NOT NULL
+        );",
+    },
+    Migration {
+        version: 7,
+        description: "Create the backup_locations table",
+        sql: "CREATE TABLE backup_locations (
+            id              INTEGER PRIMARY KEY,
+            path            TEXT NOT NULL,
+            backend_name    TEXT NOT NULL,
+            stored_at       TEXT NOT NULL
+        );",
+    },
+];
+
+pub fn get_latest_schema_version() -> u64 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+// Returns 0 for databases created before versioning was introduced.
+pub fn get_schema_version(connection: &Connection) -> Result<u64> {
+    connection
+        .execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (
+                version     INTEGER PRIMARY KEY,
+                description TEXT NOT NULL,
+                applied_at  TEXT NOT NULL
+            )",
+            [],
+        )
+        .context("Failed to create the schema_version table")?;
+
+    connection
+        .query_row(
+            "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+            [],
+            |row| row.get(0),
+        )
+        .context("Failed to read the schema version")
+}
+
+// Applies all migrations newer than the database's schema version, each one in its own transaction,
+// so that a failed migration doesn't leave the database half-upgraded.
+pub fn run_migrations(connection: &mut Connection) -> Result<()> {
+    let current_version = get_schema_version(connection)?;
+    let latest_version = get_latest_schema_version();
+    if current_version > latest_version {
+        bail!(
+            "Database schema version ({}) is newer than the latest one known to this version of the program ({})! Please update the program.",
+            current_version,
+            latest_version
+        );
+    }
+
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > current_version)
+    {
+        let context = || {
+            format!(
+                "Failed to apply migration #{} ({})",
+                migration.version, migration.description
+            )
+        };
+
+        let transaction = connection.transaction().with_context(context)?;
+        transaction
+            .execute_batch(migration.sql)
+            .with_context(context)?;
+        transaction
+            .execute(
+                "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+                rusqlite::params![
+                    migration.version,
+                    migration.description,
+                    system_time_to_str(SystemTime::now()),
+                ],
+            )
+            .with_context(context)?;
+        transaction.commit().with_context(context)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    mod migrations {
+        use super::super::*;
+
+        #[test]
+        fn are_ordered_by_version() {
+            let versions: Vec<u64> = MIGRATIONS
+                .iter()
+                .map(|migration| migration.version)
+                .collect();
+            let expected_versions: Vec<u64> = (1..=MIGRATIONS.len() as u64).collect();
+
+            assert_eq!(versions, expected_versions);
+        }
+    }
+}
@@ -1,14 +1,23 @@
+pub mod migrations;
+pub mod types;
+
 use crate::api_wrappers::APIWrapper;
 use crate::environment;
+use crate::utils::clock::{SharedClock, system_clock};
 use crate::utils::constants::{APIWrapperIdentificator, ArchiverIdentificator};
 use crate::utils::startup::create_database_directory_if_missing;
 use crate::utils::tests::is_test_environment;
 use crate::utils::time::{str_to_system_time, system_time_to_str};
+use migrations::run_migrations;
 use rusqlite::{Connection, Error::QueryReturnedNoRows};
+use std::collections::HashSet;
 use std::time::SystemTime;
+use types::{BackupRecord, BackupStatus, PendingRequest, RunOutcome, RunRecord, RunRequest};
 
 pub struct DatabaseClient {
     connection: Connection,
+    // Used for timestamps of newly added rows.
+    clock: SharedClock,
 }
 
 impl APIWrapper for DatabaseClient {
@@ -39,21 +48,27 @@ impl DatabaseClient {
                 &config.metadata.database_path
             )
         });
-        connection
-            .execute(
-                "CREATE TABLE IF NOT EXISTS schedule (
-                    id          INTEGER PRIMARY KEY,
-                    app_name    TEXT NOT NULL,
-                    next_run    TEXT NOT NULL
-                )",
-                [],
-            )
-            .expect("Failed to initialize database!");
 
-        DatabaseClient { connection }
+        Self::from_connection(connection)
     }
 
-    pub fn get_next_run_by_app_name(&self, app_name: ArchiverIdentificator) -> SystemTime {
+    // Useful mostly in tests, where an in-memory database can be passed.
+    pub fn from_connection(mut connection: Connection) -> Self {
+        run_migrations(&mut connection)
+            .unwrap_or_else(|error| panic!("Failed to migrate the database! {:#}", error));
+        DatabaseClient {
+            connection,
+            clock: system_clock(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // Returns `None` if the task has never been scheduled before.
+    pub fn get_next_run_by_app_name(&self, app_name: ArchiverIdentificator) -> Option<SystemTime> {
         let next_run_query_result = self.connection.query_row(
             "SELECT next_run FROM schedule WHERE app_name = ?1",
             [app_name.as_str()],
@@ -61,8 +76,8 @@ impl DatabaseClient {
         );
 
         match next_run_query_result {
-            Ok(next_run_string) => str_to_system_time(next_run_string),
-            Err(QueryReturnedNoRows) => SystemTime::now(),
+            Ok(next_run_string) => Some(str_to_system_time(next_run_string)),
+            Err(QueryReturnedNoRows) => None,
             Err(e) => panic!("Failed to get `next_run` timestamp: {e}"),
         }
     }
@@ -86,4 +101,517 @@ impl DatabaseClient {
                 .expect("Failed to insert next_run!");
         }
     }
+
+    pub fn get_pending_request(&self, app_name: ArchiverIdentificator) -> Option<PendingRequest> {
+        let app_name_string = app_name.as_str();
+        let pending_request_query_result = self.connection.query_row(
+            "SELECT requested_at FROM pending_requests WHERE app_name = ?1",
+            [app_name_string.clone()],
+            |row| row.get::<_, String>(0),
+        );
+
+        match pending_request_query_result {
+            Ok(requested_at_string) => Some(PendingRequest {
+                app_name: app_name_string,
+                requested_at: str_to_system_time(requested_at_string),
+            }),
+            Err(QueryReturnedNoRows) => None,
+            Err(e) => panic!("Failed to get the pending request: {e}"),
+        }
+    }
+
+    pub fn add_pending_request(&self, app_name: ArchiverIdentificator, requested_at: SystemTime) {
+        self.connection
+            .execute(
+                "INSERT INTO pending_requests (app_name, requested_at) VALUES (?1, ?2)",
+                [app_name.as_str(), system_time_to_str(requested_at)],
+            )
+            .expect("Failed to insert the pending request!");
+    }
+
+    pub fn remove_pending_request(&self, app_name: ArchiverIdentificator) {
+        self.connection
+            .execute(
+                "DELETE FROM pending_requests WHERE app_name = ?1",
+                [app_name.as_str()],
+            )
+            .expect("Failed to remove the pending request!");
+    }
+
+    pub fn is_mail_handled(&self, message_id: &str) -> bool {
+        let amount_of_rows: u64 = self
+            .connection
+            .query_row(
+                "SELECT COUNT(*) FROM handled_mails WHERE message_id = ?1",
+                [message_id],
+                |row| row.get(0),
+            )
+            .expect("Failed to check whether the mail has been handled!");
+
+        amount_of_rows > 0
+    }
+
+    // IDs of all handled mails, so that they don't have to be fetched again.
+    pub fn get_handled_mail_ids(&self) -> HashSet<String> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT message_id FROM handled_mails")
+            .expect("Failed to prepare the handled mails query!");
+        let rows = statement
+            .query_map([], |row| row.get(0))
+            .expect("Failed to get handled mails!");
+
+        rows.map(|row| row.expect("Failed to read a handled mail!"))
+            .collect()
+    }
+
+    pub fn add_handled_mail(&self, app_name: ArchiverIdentificator, message_id: &str) {
+        self.connection
+            .execute(
+                "INSERT INTO handled_mails (app_name, message_id, handled_at) VALUES (?1, ?2, ?3)",
+                [
+                    app_name.as_str(),
+                    message_id.to_string(),
+                    system_time_to_str(self.clock.now()),
+                ],
+            )
+            .expect("Failed to insert the handled mail!");
+    }
+
+    // Returns the most recent backup that has actually been uploaded.
+    pub fn get_last_stored_backup(&self, app_name: ArchiverIdentificator) -> Option<BackupRecord> {
+        let app_name_string = app_name.as_str();
+        let backup_query_result = self.connection.query_row(
+            "SELECT path, content_hash, size, created_at FROM backups
+                WHERE app_name = ?1 AND status = ?2 ORDER BY id DESC LIMIT 1",
+            [
+                app_name_string.clone(),
+                BackupStatus::Stored.as_str().to_string(),
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, u64>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            },
+        );
+
+        match backup_query_result {
+            Ok((path, content_hash, size, created_at_string)) => Some(BackupRecord {
+                app_name: app_name_string,
+                path,
+                content_hash,
+                size,
+                status: BackupStatus::Stored,
+                created_at: str_to_system_time(created_at_string),
+            }),
+            Err(QueryReturnedNoRows) => None,
+            Err(e) => panic!("Failed to get the last stored backup: {e}"),
+        }
+    }
+
+    pub fn add_backup_record(&self, record: &BackupRecord) {
+        self.connection
+            .execute(
+                "INSERT INTO backups (app_name, path, content_hash, size, status, created_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    record.app_name,
+                    record.path,
+                    record.content_hash,
+                    record.size,
+                    record.status.as_str(),
+                    system_time_to_str(record.created_at),
+                ],
+            )
+            .expect("Failed to insert the backup record!");
+    }
+
+    // Returns all backups that have actually been uploaded, oldest first.
+    pub fn get_stored_backups(&self) -> Vec<BackupRecord> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT app_name, path, content_hash, size, created_at FROM backups
+                    WHERE status = ?1 ORDER BY id",
+            )
+            .expect("Failed to prepare the stored backups query!");
+        let rows = statement
+            .query_map([BackupStatus::Stored.as_str()], |row| {
+                Ok(BackupRecord {
+                    app_name: row.get(0)?,
+                    path: row.get(1)?,
+                    content_hash: row.get(2)?,
+                    size: row.get(3)?,
+                    status: BackupStatus::Stored,
+                    created_at: str_to_system_time(row.get(4)?),
+                })
+            })
+            .expect("Failed to get stored backups!");
+
+        rows.map(|row| row.expect("Failed to read a stored backup!"))
+            .collect()
+    }
+
+    // Records that a backup has been uploaded to a storage backend.
+    pub fn add_backup_location(&self, path: &str, backend_name: &str) {
+        self.connection
+            .execute(
+                "INSERT INTO backup_locations (path, backend_name, stored_at) VALUES (?1, ?2, ?3)",
+                [path, backend_name, &system_time_to_str(self.clock.now())],
+            )
+            .expect("Failed to insert the backup location!");
+    }
+
+    // Returns names of the storage backends holding the backup. Empty for backups stored before
+    // locations were recorded.
+    pub fn get_backup_locations(&self, path: &str) -> Vec<String> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT backend_name FROM backup_locations WHERE path = ?1
+                    GROUP BY backend_name ORDER BY MIN(id)",
+            )
+            .expect("Failed to prepare the backup locations query!");
+        let rows = statement
+            .query_map([path], |row| row.get(0))
+            .expect("Failed to get backup locations!");
+
+        rows.map(|row| row.expect("Failed to read a backup location!"))
+            .collect()
+    }
+
+    // Records that the retention policy has removed a backup from a storage backend. Once no backend
+    // holds it anymore, it's marked as pruned, so that it's no longer expected to be found in
+    // storage.
+    pub fn mark_backup_pruned(&self, path: &str, backend_name: &str) {
+        self.connection
+            .execute(
+                "DELETE FROM backup_locations WHERE path = ?1 AND backend_name = ?2",
+                [path, backend_name],
+            )
+            .expect("Failed to remove the backup location!");
+        self.connection
+            .execute(
+                "UPDATE backups SET status = ?1 WHERE path = ?2 AND status = ?3
+                    AND NOT EXISTS (SELECT 1 FROM backup_locations WHERE path = ?2)",
+                [
+                    BackupStatus::Pruned.as_str(),
+                    path,
+                    BackupStatus::Stored.as_str(),
+                ],
+            )
+            .expect("Failed to mark the backup as pruned!");
+    }
+
+    // Returns the most recent backup record, no matter whether it has been uploaded or not.
+    pub fn get_last_backup_record(&self, app_name: &str) -> Option<BackupRecord> {
+        let backup_query_result = self.connection.query_row(
+            "SELECT path, content_hash, size, status, created_at FROM backups
+                WHERE app_name = ?1 ORDER BY id DESC LIMIT 1",
+            [app_name],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, u64>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            },
+        );
+
+        match backup_query_result {
+            Ok((path, content_hash, size, status, created_at_string)) => Some(BackupRecord {
+                app_name: app_name.to_string(),
+                path,
+                content_hash,
+                size,
+                status: BackupStatus::from_db_str(&status),
+                created_at: str_to_system_time(created_at_string),
+            }),
+            Err(QueryReturnedNoRows) => None,
+            Err(e) => panic!("Failed to get the last backup record: {e}"),
+        }
+    }
+
+    // Records the start of a run, and returns its ID, which is later passed to `finish_run`.
+    pub fn start_run(&self, app_name: &str, started_at: SystemTime) -> i64 {
+        self.connection
+            .execute(
+                "INSERT INTO runs (app_name, started_at, outcome) VALUES (?1, ?2, ?3)",
+                [
+                    app_name,
+                    &system_time_to_str(started_at),
+                    RunOutcome::Running.as_str(),
+                ],
+            )
+            .expect("Failed to insert the run!");
+        self.connection.last_insert_rowid()
+    }
+
+    // Only the outcome related fields of `run` are saved.
+    pub fn finish_run(&self, run: &RunRecord) {
+        self.connection
+            .execute(
+                "UPDATE runs SET finished_at = ?1, outcome = ?2, error_message = ?3,
+                    bytes_uploaded = ?4, storage_path = ?5 WHERE id = ?6",
+                rusqlite::params![
+                    run.finished_at.map(system_time_to_str),
+                    run.outcome.as_str(),
+                    run.error_message,
+                    run.bytes_uploaded,
+                    run.storage_path,
+                    run.id,
+                ],
+            )
+            .expect("Failed to update the run!");
+    }
+
+    // Returns at most `limit` runs, newest first. If `app_name` is passed, only runs of that task.
+    pub fn get_runs(&self, app_name: Option<&str>, limit: u64) -> Vec<RunRecord> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT id, app_name, started_at, finished_at, outcome, error_message,
+                    bytes_uploaded, storage_path FROM runs
+                    WHERE ?1 IS NULL OR app_name = ?1 ORDER BY id DESC LIMIT ?2",
+            )
+            .expect("Failed to prepare the runs query!");
+        let rows = statement
+            .query_map(rusqlite::params![app_name, limit], |row| {
+                Ok(RunRecord {
+                    id: row.get(0)?,
+                    app_name: row.get(1)?,
+                    started_at: str_to_system_time(row.get(2)?),
+                    finished_at: row.get::<_, Option<String>>(3)?.map(str_to_system_time),
+                    outcome: RunOutcome::from_db_str(&row.get::<_, String>(4)?),
+                    error_message: row.get(5)?,
+                    bytes_uploaded: row.get(6)?,
+                    storage_path: row.get(7)?,
+                })
+            })
+            .expect("Failed to get runs!");
+
+        rows.map(|row| row.expect("Failed to read a run!"))
+            .collect()
+    }
+
+    pub fn add_run_request(&self, app_name: &str, reschedule: bool) {
+        self.connection
+            .execute(
+                "INSERT INTO run_requests (app_name, reschedule, requested_at) VALUES (?1, ?2, ?3)",
+                rusqlite::params![app_name, reschedule, system_time_to_str(self.clock.now())],
+            )
+            .expect("Failed to insert the run request!");
+    }
+
+    // Removes all run requests of the app, and returns the oldest one. Requests made while waiting
+    // for the task to run are merged, rescheduling it if any of them asked to. Both happen in one
+    // statement, so that requests made in the meantime by another process aren't lost.
+    pub fn take_run_request(&self, app_name: &str) -> Option<RunRequest> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "DELETE FROM run_requests WHERE app_name = ?1 RETURNING reschedule, requested_at",
+            )
+            .expect("Failed to prepare the run requests query!");
+        let rows = statement
+            .query_map([app_name], |row| {
+                Ok((
+                    row.get::<_, bool>(0)?,
+                    str_to_system_time(row.get::<_, String>(1)?),
+                ))
+            })
+            .expect("Failed to remove run requests!");
+
+        rows.map(|row| row.expect("Failed to read a run request!"))
+            .reduce(
+                |(reschedule, requested_at), (other_reschedule, other_requested_at)| {
+                    (
+                        reschedule || other_reschedule,
+                        requested_at.min(other_requested_at),
+                    )
+                },
+            )
+            .map(|(reschedule, requested_at)| RunRequest {
+                app_name: app_name.to_string(),
+                reschedule,
+                requested_at,
+            })
+    }
+
+    pub fn is_task_paused(&self, app_name: &str) -> bool {
+        let amount_of_rows: u64 = self
+            .connection
+            .query_row(
+                "SELECT COUNT(*) FROM paused_tasks WHERE app_name = ?1",
+                [app_name],
+                |row| row.get(0),
+            )
+            .expect("Failed to check whether the task is paused!");
+
+        amount_of_rows > 0
+    }
+
+    pub fn set_task_paused(&self, app_name: &str, is_paused: bool) {
+        if is_paused {
+            self.connection
+                .execute(
+                    "INSERT OR IGNORE INTO paused_tasks (app_name, paused_at) VALUES (?1, ?2)",
+                    [app_name, &system_time_to_str(self.clock.now())],
+                )
+                .expect("Failed to pause the task!");
+        } else {
+            self.connection
+                .execute("DELETE FROM paused_tasks WHERE app_name = ?1", [app_name])
+                .expect("Failed to resume the task!");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod runs {
+        use super::super::*;
+        use crate::utils::clock::{Clock, FakeClock};
+        use std::time::Duration;
+
+        #[test]
+        fn records_and_finishes_runs() {
+            let database = DatabaseClient::from_connection(Connection::open_in_memory().unwrap());
+            let started_at = FakeClock::default().now();
+            let first_id = database.start_run("first", started_at);
+            database.start_run("second", started_at);
+
+            database.finish_run(&RunRecord {
+                id: first_id,
+                app_name: "first".to_string(),
+                started_at,
+                finished_at: Some(started_at + Duration::from_secs(60)),
+                outcome: RunOutcome::Failed,
+                error_message: Some("Something went wrong".to_string()),
+                bytes_uploaded: None,
+                storage_path: None,
+            });
+
+            let all_runs = database.get_runs(None, 10);
+            let first_runs = database.get_runs(Some("first"), 10);
+            assert_eq!(all_runs.len(), 2);
+            assert_eq!(all_runs[0].app_name, "second");
+            assert_eq!(all_runs[0].outcome, RunOutcome::Running);
+            assert_eq!(first_runs.len(), 1);
+            assert_eq!(first_runs[0].outcome, RunOutcome::Failed);
+            assert_eq!(
+                first_runs[0].error_message.as_deref(),
+                Some("Something went wrong")
+            );
+            assert!(first_runs[0].finished_at.is_some());
+        }
+    }
+
+    mod backups {
+        use super::super::*;
+        use crate::utils::clock::{Clock, FakeClock};
+
+        #[test]
+        fn leaves_out_pruned_backups() {
+            let database = DatabaseClient::from_connection(Connection::open_in_memory().unwrap());
+            let clock = FakeClock::default();
+            for path in ["tests/2025-01-01T00:00:00", "tests/2025-01-02T00:00:00"] {
+                database.add_backup_record(&BackupRecord {
+                    app_name: "tests".to_string(),
+                    path: path.to_string(),
+                    content_hash: "hash".to_string(),
+                    size: 4,
+                    status: BackupStatus::Stored,
+                    created_at: clock.now(),
+                });
+                database.add_backup_location(path, "local");
+            }
+
+            database.mark_backup_pruned("tests/2025-01-01T00:00:00", "local");
+
+            let stored_backups = database.get_stored_backups();
+            assert_eq!(stored_backups.len(), 1);
+            assert_eq!(stored_backups[0].path, "tests/2025-01-02T00:00:00");
+            assert_eq!(
+                database.get_last_backup_record("tests").unwrap().status,
+                BackupStatus::Stored
+            );
+        }
+
+        #[test]
+        fn records_backends_holding_backups() {
+            let database = DatabaseClient::from_connection(Connection::open_in_memory().unwrap());
+            database.add_backup_location("tests/2025-01-01T00:00:00", "local");
+            database.add_backup_location("tests/2025-01-01T00:00:00", "s3");
+            database.add_backup_location("tests/2025-01-02T00:00:00", "s3");
+
+            assert_eq!(
+                database.get_backup_locations("tests/2025-01-01T00:00:00"),
+                vec!["local", "s3"]
+            );
+            assert!(database.get_backup_locations("other").is_empty());
+        }
+
+        #[test]
+        fn keeps_backups_until_pruned_from_every_backend() {
+            let database = DatabaseClient::from_connection(Connection::open_in_memory().unwrap());
+            let path = "tests/2025-01-01T00:00:00";
+            database.add_backup_record(&BackupRecord {
+                app_name: "tests".to_string(),
+                path: path.to_string(),
+                content_hash: "hash".to_string(),
+                size: 4,
+                status: BackupStatus::Stored,
+                created_at: FakeClock::default().now(),
+            });
+            database.add_backup_location(path, "local");
+            database.add_backup_location(path, "s3");
+
+            database.mark_backup_pruned(path, "local");
+            assert_eq!(database.get_stored_backups().len(), 1);
+            assert_eq!(database.get_backup_locations(path), vec!["s3"]);
+
+            database.mark_backup_pruned(path, "s3");
+            assert!(database.get_stored_backups().is_empty());
+        }
+    }
+
+    mod run_requests {
+        use super::super::*;
+
+        #[test]
+        fn merges_requests_of_the_same_app() {
+            let database = DatabaseClient::from_connection(Connection::open_in_memory().unwrap());
+            database.add_run_request("first", false);
+            database.add_run_request("first", true);
+            database.add_run_request("second", false);
+
+            let first_request = database.take_run_request("first").unwrap();
+            assert!(first_request.reschedule);
+            assert!(database.take_run_request("first").is_none());
+            assert!(!database.take_run_request("second").unwrap().reschedule);
+        }
+    }
+
+    mod paused_tasks {
+        use super::super::*;
+
+        #[test]
+        fn pauses_and_resumes_tasks() {
+            let database = DatabaseClient::from_connection(Connection::open_in_memory().unwrap());
+            database.set_task_paused("first", true);
+            database.set_task_paused("first", true);
+
+            assert!(database.is_task_paused("first"));
+            assert!(!database.is_task_paused("second"));
+
+            database.set_task_paused("first", false);
+            assert!(!database.is_task_paused("first"));
+        }
+    }
 }
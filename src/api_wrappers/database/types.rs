@@ -0,0 +1,102 @@
+use std::time::SystemTime;
+
+// A data export that has been requested from a platform, but hasn't been archived yet.
+#[derive(Clone, Debug)]
+pub struct PendingRequest {
+    pub app_name: String,
+    pub requested_at: SystemTime,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BackupStatus {
+    // The export has been uploaded to storage.
+    Stored,
+    // The export was identical to the previously stored one, so it hasn't been uploaded.
+    Unchanged,
+    // The export has been uploaded, but later removed from storage by the retention policy.
+    Pruned,
+}
+
+impl BackupStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stored => "stored",
+            Self::Unchanged => "unchanged",
+            Self::Pruned => "pruned",
+        }
+    }
+
+    pub fn from_db_str(value: &str) -> Self {
+        match value {
+            "unchanged" => Self::Unchanged,
+            "pruned" => Self::Pruned,
+            _ => Self::Stored,
+        }
+    }
+}
+
+// Outcome of a single archiving run, kept so that it can be audited later.
+#[derive(Clone, Debug)]
+pub struct BackupRecord {
+    pub app_name: String,
+    // Directory of the backup. For unchanged exports, it's the one of the identical stored backup.
+    pub path: String,
+    pub content_hash: String,
+    pub size: u64,
+    pub status: BackupStatus,
+    pub created_at: SystemTime,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RunOutcome {
+    // Hasn't finished yet, or the program has been stopped in the middle of it.
+    Running,
+    Succeeded,
+    Failed,
+    // Has been cancelled after exceeding the task's timeout.
+    TimedOut,
+}
+
+impl RunOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::TimedOut => "timed out",
+        }
+    }
+
+    pub fn from_db_str(value: &str) -> Self {
+        match value {
+            "succeeded" => Self::Succeeded,
+            "failed" => Self::Failed,
+            "timed out" => Self::TimedOut,
+            _ => Self::Running,
+        }
+    }
+}
+
+// A single execution of a task. Every retry attempt is a separate run.
+#[derive(Clone, Debug)]
+pub struct RunRecord {
+    pub id: i64,
+    pub app_name: String,
+    pub started_at: SystemTime,
+    pub finished_at: Option<SystemTime>,
+    pub outcome: RunOutcome,
+    pub error_message: Option<String>,
+    // Both are known only if the run has stored a backup. Unchanged exports upload 0 bytes.
+    pub bytes_uploaded: Option<u64>,
+    pub storage_path: Option<String>,
+}
+
+// A request to run a task right away, made from the TUI or the `run-now` option, and picked up by
+// the Scheduler.
+#[derive(Clone, Debug)]
+pub struct RunRequest {
+    pub app_name: String,
+    // Whether the regular schedule should start counting from this run.
+    pub reschedule: bool,
+    pub requested_at: SystemTime,
+}
@@ -0,0 +1,215 @@
+use crate::api_wrappers::APIWrapper;
+use crate::api_wrappers::storage::StorageBackend;
+use crate::api_wrappers::storage::types::StoredObject;
+use crate::environment;
+use crate::utils::constants::APIWrapperIdentificator;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+
+// Stores backups in a plain directory on the local filesystem, using the same layout as S3.
+pub struct LocalStorageClient {
+    root: PathBuf,
+}
+
+impl APIWrapper for LocalStorageClient {
+    fn get_identificator(&self) -> APIWrapperIdentificator {
+        APIWrapperIdentificator::LocalStorage
+    }
+}
+
+impl Default for LocalStorageClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalStorageClient {
+    pub fn new() -> Self {
+        let config = environment::environment();
+        Self::from_path(PathBuf::from(&config.storage.local_path))
+    }
+
+    pub fn from_path(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    // Recursively collects all files in the directory. Paths are relative to the root, and always
+    // use `/` as the separator, so that they match the ones used by S3.
+    async fn collect_files(&self, directory_path: &Path) -> Result<Vec<StoredObject>> {
+        let mut objects = Vec::new();
+        let mut directories = vec![directory_path.to_path_buf()];
+
+        while let Some(directory_path) = directories.pop() {
+            let mut entries = match fs::read_dir(&directory_path).await {
+                Ok(entries) => entries,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(error) => {
+                    return Err(error)
+                        .with_context(|| format!("Failed to read {}!", directory_path.display()));
+                }
+            };
+
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .with_context(|| format!("Failed to read {}!", directory_path.display()))?
+            {
+                let metadata = entry.metadata().await?;
+                let entry_path = entry.path();
+                if metadata.is_dir() {
+                    directories.push(entry_path);
+                    continue;
+                }
+
+                let relative_path = entry_path.strip_prefix(&self.root)?;
+                let path = relative_path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                objects.push(StoredObject {
+                    path,
+                    size: metadata.len(),
+                });
+            }
+        }
+
+        Ok(objects)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalStorageClient {
+    async fn upload(
+        &self,
+        path: &str,
+        filename: &str,
+        data: &mut (dyn AsyncRead + Unpin + Send),
+    ) -> Result<()> {
+        let directory_path = self.root.join(path);
+        fs::create_dir_all(&directory_path)
+            .await
+            .with_context(|| format!("Failed to create {}!", directory_path.display()))?;
+
+        let file_path = directory_path.join(filename);
+        let mut file = fs::File::create(&file_path)
+            .await
+            .with_context(|| format!("Failed to create {}!", file_path.display()))?;
+        tokio::io::copy(data, &mut file)
+            .await
+            .with_context(|| format!("Failed to write {}!", file_path.display()))?;
+        file.flush()
+            .await
+            .with_context(|| format!("Failed to write {}!", file_path.display()))?;
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>> {
+        // Only the directory part of the prefix can be used to narrow down the search.
+        let directory = match prefix.rfind('/') {
+            Some(index) => &prefix[..index],
+            None => "",
+        };
+
+        let mut objects = self.collect_files(&self.root.join(directory)).await?;
+        objects.retain(|object| object.path.starts_with(prefix));
+        objects.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(objects)
+    }
+
+    async fn download(&self, path: &str, data: &mut (dyn AsyncWrite + Unpin + Send)) -> Result<()> {
+        let file_path = self.root.join(path);
+        let mut file = fs::File::open(&file_path)
+            .await
+            .with_context(|| format!("Failed to open {}!", file_path.display()))?;
+        tokio::io::copy(&mut file, data)
+            .await
+            .with_context(|| format!("Failed to read {}!", file_path.display()))?;
+        data.flush().await?;
+        Ok(())
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        let file_path = self.root.join(path);
+        fs::remove_file(&file_path)
+            .await
+            .with_context(|| format!("Failed to delete {}!", file_path.display()))?;
+
+        // Backups are kept in separate directories, which would otherwise be left behind empty.
+        // Removing a directory that still contains something fails, which is fine.
+        if let Some(directory_path) = file_path.parent()
+            && directory_path != self.root
+        {
+            fs::remove_dir(directory_path).await.ok();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod local_storage_client {
+        use super::super::*;
+        use crate::utils::uuid;
+
+        #[tokio::test]
+        async fn uploads_files_using_backup_layout() {
+            let root = std::env::temp_dir().join(format!("asmda_{}", uuid::get_random()));
+            let client = LocalStorageClient::from_path(root.clone());
+
+            client
+                .upload(
+                    "tests/2025-01-01T00:00:00",
+                    "backup.zip",
+                    &mut b"data".as_slice(),
+                )
+                .await
+                .unwrap();
+
+            let file_path = root.join("tests/2025-01-01T00:00:00/backup.zip");
+            assert_eq!(std::fs::read(file_path).unwrap(), b"data".to_vec());
+            std::fs::remove_dir_all(root).unwrap();
+        }
+
+        #[tokio::test]
+        async fn lists_downloads_and_deletes_files() {
+            let root = std::env::temp_dir().join(format!("asmda_{}", uuid::get_random()));
+            let client = LocalStorageClient::from_path(root.clone());
+            for path in [
+                "tests/2025-01-01T00:00:00",
+                "tests/2025-01-02T00:00:00",
+                "other/x",
+            ] {
+                client
+                    .upload(path, "backup.zip", &mut b"data".as_slice())
+                    .await
+                    .unwrap();
+            }
+
+            let objects = client.list("tests/").await.unwrap();
+            let mut data = Vec::new();
+            client.download(&objects[0].path, &mut data).await.unwrap();
+            assert_eq!(data, b"data".to_vec());
+            let paths: Vec<&str> = objects.iter().map(|object| object.path.as_str()).collect();
+            assert_eq!(
+                paths,
+                vec![
+                    "tests/2025-01-01T00:00:00/backup.zip",
+                    "tests/2025-01-02T00:00:00/backup.zip"
+                ]
+            );
+            assert_eq!(objects[0].size, 4);
+
+            client
+                .delete("tests/2025-01-01T00:00:00/backup.zip")
+                .await
+                .unwrap();
+            assert_eq!(client.list("tests/").await.unwrap().len(), 1);
+            assert!(!root.join("tests/2025-01-01T00:00:00").exists());
+            std::fs::remove_dir_all(root).unwrap();
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use crate::api_wrappers::mail::types::{MailMessage, MailRule};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+
+// Characters that can't be a part of an URL found in plain text or HTML.
+static URL_TERMINATORS: &[char] = &[
+    '"', '\'', '<', '>', '(', ')', '[', ']', ' ', '\t', '\r', '\n',
+];
+
+fn split_headers_and_body(raw: &str) -> (&str, &str) {
+    // Parts of multipart messages may have no headers at all.
+    if let Some(body) = raw.strip_prefix("\r\n").or_else(|| raw.strip_prefix('\n')) {
+        return ("", body);
+    }
+    if let Some(index) = raw.find("\r\n\r\n") {
+        return (&raw[..index], &raw[index + 4..]);
+    }
+    if let Some(index) = raw.find("\n\n") {
+        return (&raw[..index], &raw[index + 2..]);
+    }
+    (raw, "")
+}
+
+// Returns the value of a header, with folded (multiline) values joined back together.
+fn get_header(headers: &str, name: &str) -> Option<String> {
+    let mut value: Option<String> = None;
+
+    for line in headers.lines() {
+        let is_continuation = line.starts_with(' ') || line.starts_with('\t');
+        if is_continuation {
+            if let Some(current_value) = value.as_mut() {
+                current_value.push(' ');
+                current_value.push_str(line.trim());
+            }
+            continue;
+        }
+        if value.is_some() {
+            break;
+        }
+
+        if let Some((key, header_value)) = line.split_once(':')
+            && key.trim().eq_ignore_ascii_case(name)
+        {
+            value = Some(header_value.trim().to_string());
+        }
+    }
+
+    value
+}
+
+// Decodes the `quoted-printable` transfer encoding used by most HTML emails.
+pub fn decode_quoted_printable(encoded: &str) -> String {
+    let bytes = encoded.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] != b'=' {
+            decoded.push(bytes[index]);
+            index += 1;
+            continue;
+        }
+
+        // Soft line breaks.
+        if bytes[index + 1..].starts_with(b"\r\n") {
+            index += 3;
+            continue;
+        }
+        if bytes[index + 1..].starts_with(b"\n") {
+            index += 2;
+            continue;
+        }
+
+        let hex = encoded.get(index + 1..index + 3).unwrap_or("");
+        match u8::from_str_radix(hex, 16) {
+            Ok(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            Err(_) => {
+                decoded.push(b'=');
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// Data that isn't valid base64 is returned as is.
+fn decode_base64(encoded: &str) -> String {
+    let encoded: String = encoded.split_whitespace().collect();
+    match BASE64.decode(&encoded) {
+        Ok(decoded) => String::from_utf8_lossy(&decoded).to_string(),
+        Err(_) => encoded,
+    }
+}
+
+// Returns a parameter of a header value, e.g. `boundary` of `multipart/alternative; boundary=x`.
+fn get_header_parameter(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|parameter| {
+        let (key, parameter_value) = parameter.split_once('=')?;
+        if !key.trim().eq_ignore_ascii_case(name) {
+            return None;
+        }
+        Some(parameter_value.trim().trim_matches('"').to_string())
+    })
+}
+
+// Returns the parts found between the boundaries, leaving out the preamble and the epilogue.
+fn split_multipart<'a>(body: &'a str, boundary: &str) -> Vec<&'a str> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+
+    for part in body.split(delimiter.as_str()).skip(1) {
+        // The closing boundary is followed by two hyphens.
+        if part.starts_with("--") {
+            break;
+        }
+        // Line breaks around boundaries belong to them.
+        let part = part.split_once('\n').map_or("", |(_, part)| part);
+        let part = part
+            .strip_suffix("\r\n")
+            .or_else(|| part.strip_suffix('\n'))
+            .unwrap_or(part);
+        parts.push(part);
+    }
+
+    parts
+}
+
+// Returns the text of a MIME part, decoded according to its own headers. Text of multipart ones
+// is joined, so that links can be found in any of their parts. Other parts, e.g. images, are left
+// out.
+fn decode_part(headers: &str, body: &str) -> String {
+    // Boundaries are case-sensitive, so only the type itself is lowercased.
+    let content_type =
+        get_header(headers, "Content-Type").unwrap_or_else(|| "text/plain".to_string());
+    let media_type = content_type.to_lowercase();
+
+    if media_type.starts_with("multipart/") {
+        let Some(boundary) = get_header_parameter(&content_type, "boundary") else {
+            return String::new();
+        };
+        return split_multipart(body, &boundary)
+            .into_iter()
+            .map(|part| {
+                let (part_headers, part_body) = split_headers_and_body(part);
+                decode_part(part_headers, part_body)
+            })
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    if !media_type.starts_with("text/") {
+        return String::new();
+    }
+
+    let transfer_encoding = get_header(headers, "Content-Transfer-Encoding").unwrap_or_default();
+    match transfer_encoding.to_lowercase().as_str() {
+        "quoted-printable" => decode_quoted_printable(body),
+        "base64" => decode_base64(body),
+        _ => body.to_string(),
+    }
+}
+
+// Returns the `Message-ID` header, or an ID made up of the mailbox and the UID if it's missing.
+pub fn get_message_id(headers: &[u8], uid: u32, mailbox: &str) -> String {
+    let headers = String::from_utf8_lossy(headers);
+    get_header(&headers, "Message-ID").unwrap_or_else(|| format!("{}:{}", mailbox, uid))
+}
+
+pub fn parse_message(raw: &[u8], uid: u32, mailbox: &str) -> MailMessage {
+    let raw_string = String::from_utf8_lossy(raw).to_string();
+    let (headers, body) = split_headers_and_body(&raw_string);
+
+    MailMessage {
+        id: get_message_id(headers.as_bytes(), uid, mailbox),
+        uid,
+        from: get_header(headers, "From").unwrap_or_default(),
+        subject: get_header(headers, "Subject").unwrap_or_default(),
+        body: decode_part(headers, body),
+    }
+}
+
+pub fn extract_download_url(message: &MailMessage, rule: &MailRule) -> Option<String> {
+    let start = message.body.find(&rule.url_prefix)?;
+    let url_with_rest = &message.body[start..];
+    let end = url_with_rest
+        .find(URL_TERMINATORS)
+        .unwrap_or(url_with_rest.len());
+
+    // Links in HTML emails have their ampersands escaped.
+    let url = url_with_rest[..end].replace("&amp;", "&");
+    Some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    mod message {
+        use super::super::*;
+
+        static TEST_MESSAGE: &str = "From: Platform <no-reply@platform.com>\r
+Subject: Your data is\r
+ ready to download\r
+Message-ID: <123@platform.com>\r
+Content-Type: text/html; charset=\"utf-8\"\r
+Content-Transfer-Encoding: quoted-printable\r
+\r
+<p>Hi!</p><a href=3D\"https://platform.com/download?id=3D1&amp;token=3Dab=\r
+cd\">Download</a>\r
+";
+
+        fn get_test_rule() -> MailRule {
+            MailRule {
+                sender: "no-reply@platform.com".to_string(),
+                subject: "Your data is ready".to_string(),
+                url_prefix: "https://platform.com/download".to_string(),
+            }
+        }
+
+        #[test]
+        fn parses_headers() {
+            let message = parse_message(TEST_MESSAGE.as_bytes(), 1, "INBOX");
+
+            assert_eq!(message.id, "<123@platform.com>");
+            assert_eq!(message.from, "Platform <no-reply@platform.com>");
+            assert_eq!(message.subject, "Your data is ready to download");
+        }
+
+        #[test]
+        fn generates_id_if_missing() {
+            let message = parse_message(b"Subject: Hi\r\n\r\nHello!", 5, "INBOX");
+
+            assert_eq!(message.id, "INBOX:5");
+            assert_eq!(message.body, "Hello!");
+        }
+
+        #[test]
+        fn extracts_download_url() {
+            let message = parse_message(TEST_MESSAGE.as_bytes(), 1, "INBOX");
+            let url = extract_download_url(&message, &get_test_rule());
+
+            assert_eq!(
+                url,
+                Some("https://platform.com/download?id=1&token=abcd".to_string())
+            );
+        }
+
+        #[test]
+        fn decodes_each_part_by_its_own_encoding() {
+            let message = parse_message(
+                b"Subject: Hi\r
+Content-Type: multipart/mixed; boundary=\"Outer\"\r
+\r
+Preamble mentioning Content-Transfer-Encoding: quoted-printable, a=3D\r
+--Outer\r
+Content-Type: multipart/alternative; boundary=inner\r
+\r
+--inner\r
+Content-Type: text/plain; charset=utf-8\r
+Content-Transfer-Encoding: base64\r
+\r
+aHR0cHM6Ly9wbGF0Zm9ybS5jb20vZG93bmxvYWQ/\r
+aWQ9MQ==\r
+--inner\r
+Content-Type: text/html\r
+Content-Transfer-Encoding: quoted-printable\r
+\r
+<a href=3D\"https://platform.com/download?id=3D1\">Download</a>\r
+--inner--\r
+--Outer\r
+Content-Type: image/png\r
+Content-Transfer-Encoding: base64\r
+\r
+iVBORw0KGgo=\r
+--Outer\r
+\r
+a=3D1\r
+--Outer--\r
+",
+                1,
+                "INBOX",
+            );
+
+            assert_eq!(
+                message.body,
+                "https://platform.com/download?id=1\n\
+                 <a href=\"https://platform.com/download?id=1\">Download</a>\n\
+                 a=3D1"
+            );
+            assert_eq!(
+                extract_download_url(&message, &get_test_rule()),
+                Some("https://platform.com/download?id=1".to_string())
+            );
+        }
+
+        #[test]
+        fn returns_none_without_download_url() {
+            let message = parse_message(b"Subject: Hi\r\n\r\nhttps://platform.com", 5, "INBOX");
+            let url = extract_download_url(&message, &get_test_rule());
+
+            assert_eq!(url, None);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+pub mod message;
+pub mod types;
+
+use crate::api_wrappers::APIWrapper;
+use crate::api_wrappers::database::DatabaseClient;
+use crate::environment;
+use crate::environment::types::ImapEnvironment;
+use crate::utils::constants::APIWrapperIdentificator;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use message::{extract_download_url, get_message_id, parse_message};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore, pki_types::ServerName};
+use types::{MailMessage, MailRule};
+
+trait ImapStream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> ImapStream for T {}
+
+// A single untagged server response, with literals (e.g. message bodies) separated from the line.
+struct ImapResponse {
+    line: String,
+    literals: Vec<Vec<u8>>,
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// IMAP expects dates in the `1-Jan-2025` format.
+fn system_time_to_imap_date(system_time: SystemTime) -> String {
+    let datetime: DateTime<Utc> = system_time.into();
+    datetime.format("%-d-%b-%Y").to_string()
+}
+
+// Returns the amount of bytes of a literal announced at the end of a line (`... {123}`).
+fn get_literal_length(line: &str) -> Option<usize> {
+    let line = line.trim_end();
+    if !line.ends_with('}') {
+        return None;
+    }
+
+    let start = line.rfind('{')?;
+    line[start + 1..line.len() - 1].parse().ok()
+}
+
+// A minimal IMAP client, supporting only what's needed to pick up emails sent by platforms.
+pub struct ImapClient {
+    stream: BufReader<Box<dyn ImapStream>>,
+    mailbox: String,
+    tag_counter: u32,
+}
+
+impl APIWrapper for ImapClient {
+    fn get_identificator(&self) -> APIWrapperIdentificator {
+        APIWrapperIdentificator::Mail
+    }
+}
+
+impl ImapClient {
+    pub async fn new() -> Result<Self> {
+        let config = environment::environment();
+        Self::from_config(&config.imap).await
+    }
+
+    pub async fn from_config(config: &ImapEnvironment) -> Result<Self> {
+        let address = format!("{}:{}", config.host, config.port);
+        let tcp_stream = TcpStream::connect(&address)
+            .await
+            .with_context(|| format!("Failed to connect to the IMAP server ({})!", address))?;
+
+        let stream: Box<dyn ImapStream> = if config.tls {
+            let mut root_certificates = RootCertStore::empty();
+            root_certificates.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+            let tls_config = ClientConfig::builder()
+                .with_root_certificates(root_certificates)
+                .with_no_client_auth();
+            let server_name = ServerName::try_from(config.host.clone())
+                .context("Invalid IMAP server host name!")?;
+
+            let tls_stream = TlsConnector::from(Arc::new(tls_config))
+                .connect(server_name, tcp_stream)
+                .await
+                .context("Failed to establish a TLS connection with the IMAP server!")?;
+            Box::new(tls_stream)
+        } else {
+            Box::new(tcp_stream)
+        };
+
+        let mut client = Self {
+            stream: BufReader::new(stream),
+            mailbox: config.mailbox.clone(),
+            tag_counter: 0,
+        };
+
+        let greeting = client.read_line().await?;
+        if !greeting.starts_with("* OK") && !greeting.starts_with("* PREAUTH") {
+            bail!(
+                "IMAP server refused the connection! ({})",
+                greeting.trim_end()
+            );
+        }
+
+        let username = config
+            .username
+            .clone()
+            .context("`IMAP_USERNAME` is not defined, even though `IMAP_ENABLE` is set to true!")?;
+        let password = config
+            .password
+            .clone()
+            .context("`IMAP_PASSWORD` is not defined, even though `IMAP_ENABLE` is set to true!")?;
+        client
+            .command(&format!("LOGIN {} {}", quote(&username), quote(&password)))
+            .await
+            .context("Failed to log in to the IMAP server!")?;
+        client
+            .command(&format!("SELECT {}", quote(&config.mailbox)))
+            .await
+            .with_context(|| format!("Failed to select the `{}` mailbox!", config.mailbox))?;
+
+        Ok(client)
+    }
+
+    async fn read_line(&mut self) -> Result<String> {
+        let mut buffer = Vec::new();
+        let amount_of_bytes = self
+            .stream
+            .read_until(b'\n', &mut buffer)
+            .await
+            .context("Failed to read from the IMAP server!")?;
+        if amount_of_bytes == 0 {
+            bail!("IMAP server has closed the connection!");
+        }
+
+        Ok(String::from_utf8_lossy(&buffer).to_string())
+    }
+
+    // Sends a command and returns all untagged responses, failing if the server didn't reply `OK`.
+    async fn command(&mut self, command: &str) -> Result<Vec<ImapResponse>> {
+        self.tag_counter += 1;
+        let tag = format!("A{}", self.tag_counter);
+
+        self.stream
+            .get_mut()
+            .write_all(format!("{} {}\r\n", tag, command).as_bytes())
+            .await
+            .context("Failed to write to the IMAP server!")?;
+
+        let mut responses = Vec::new();
+        loop {
+            let mut line = self.read_line().await?;
+
+            if let Some(status) = line.strip_prefix(&format!("{} ", tag)) {
+                if !status.starts_with("OK") {
+                    bail!("IMAP server responded with: {}", status.trim_end());
+                }
+                return Ok(responses);
+            }
+
+            let mut literals = Vec::new();
+            while let Some(literal_length) = get_literal_length(&line) {
+                let mut literal = vec![0; literal_length];
+                self.stream
+                    .read_exact(&mut literal)
+                    .await
+                    .context("Failed to read a literal from the IMAP server!")?;
+                literals.push(literal);
+
+                let rest_of_line = self.read_line().await?;
+                line = format!("{}{}", line.trim_end(), rest_of_line);
+            }
+
+            responses.push(ImapResponse { line, literals });
+        }
+    }
+
+    async fn search(&mut self, rule: &MailRule, since: SystemTime) -> Result<Vec<u32>> {
+        let criteria = format!(
+            "SINCE {} FROM {} SUBJECT {}",
+            system_time_to_imap_date(since),
+            quote(&rule.sender),
+            quote(&rule.subject)
+        );
+        let responses = self.command(&format!("UID SEARCH {}", criteria)).await?;
+
+        let mut uids = Vec::new();
+        for response in responses {
+            if let Some(values) = response.line.trim_end().strip_prefix("* SEARCH") {
+                uids.extend(
+                    values
+                        .split_whitespace()
+                        .filter_map(|uid| uid.parse::<u32>().ok()),
+                );
+            }
+        }
+
+        Ok(uids)
+    }
+
+    // Fetches only the `Message-ID` header, so that handled messages can be skipped cheaply.
+    async fn fetch_message_id(&mut self, uid: u32) -> Result<String> {
+        let responses = self
+            .command(&format!(
+                "UID FETCH {} (BODY.PEEK[HEADER.FIELDS (MESSAGE-ID)])",
+                uid
+            ))
+            .await?;
+
+        let headers = responses
+            .into_iter()
+            .find_map(|response| response.literals.into_iter().next())
+            .unwrap_or_default();
+        Ok(get_message_id(&headers, uid, &self.mailbox))
+    }
+
+    // Contrary to `BODY[]`, `BODY.PEEK[]` doesn't mark the message as seen.
+    async fn fetch(&mut self, uid: u32) -> Result<MailMessage> {
+        let responses = self
+            .command(&format!("UID FETCH {} BODY.PEEK[]", uid))
+            .await?;
+
+        let raw_message = responses
+            .into_iter()
+            .find_map(|response| response.literals.into_iter().next())
+            .with_context(|| format!("IMAP server didn't return the message with UID {}!", uid))?;
+
+        Ok(parse_message(&raw_message, uid, &self.mailbox))
+    }
+
+    // Returns messages matching the rule, received after `since`. Newest messages come first.
+    // Messages whose IDs are in `handled_ids` aren't fetched.
+    pub async fn fetch_matching_messages(
+        &mut self,
+        rule: &MailRule,
+        since: SystemTime,
+        handled_ids: &HashSet<String>,
+    ) -> Result<Vec<MailMessage>> {
+        let mut uids = self.search(rule, since).await?;
+        uids.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut messages = Vec::new();
+        for uid in uids {
+            if handled_ids.contains(&self.fetch_message_id(uid).await?) {
+                continue;
+            }
+            messages.push(self.fetch(uid).await?);
+        }
+
+        Ok(messages)
+    }
+
+    pub async fn logout(mut self) {
+        // We're done with the server either way, there's no point in handling errors here.
+        self.command("LOGOUT").await.ok();
+    }
+}
+
+// Picks the first message, that hasn't been handled before and contains a download URL.
+pub fn pick_unhandled_download_url(
+    messages: Vec<MailMessage>,
+    rule: &MailRule,
+    database: &DatabaseClient,
+) -> Option<(MailMessage, String)> {
+    messages
+        .into_iter()
+        .filter(|message| !database.is_mail_handled(&message.id))
+        .find_map(|message| {
+            let url = extract_download_url(&message, rule)?;
+            Some((message, url))
+        })
+}
@@ -0,0 +1,21 @@
+// Describes how to recognize the email that a platform sends when the requested data is ready.
+#[derive(Clone, Debug)]
+pub struct MailRule {
+    // Matched against the `From` header (substring, case-insensitive, done by the IMAP server).
+    pub sender: String,
+    // Matched against the `Subject` header (substring, case-insensitive, done by the IMAP server).
+    pub subject: String,
+    // The download link is the first URL in the message's body starting with this prefix.
+    pub url_prefix: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct MailMessage {
+    // Value of the `Message-ID` header, or a value generated from the mailbox and message's UID if
+    // that's missing. Used to remember which messages have already been handled.
+    pub id: String,
+    pub uid: u32,
+    pub from: String,
+    pub subject: String,
+    pub body: String,
+}
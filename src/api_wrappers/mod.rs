@@ -1,6 +1,9 @@
 pub mod browser;
 pub mod database;
+pub mod local;
+pub mod mail;
 pub mod s3;
+pub mod storage;
 
 use crate::utils::constants::APIWrapperIdentificator;
 
@@ -1,11 +1,12 @@
-pub mod utils;
-
 use crate::api_wrappers::APIWrapper;
-use crate::environment;
-use crate::logger::logger;
+use crate::api_wrappers::storage::StorageBackend;
+use crate::api_wrappers::storage::types::StoredObject;
+use crate::environment::types::S3Environment;
 use crate::utils::constants::APIWrapperIdentificator;
-use crate::utils::exit::exit;
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
 use s3::{Bucket, Region, creds::Credentials};
+use tokio::io::{AsyncRead, AsyncWrite};
 
 pub struct S3Client {
     bucket: Bucket,
@@ -17,47 +18,105 @@ impl APIWrapper for S3Client {
     }
 }
 
-async fn get_main_bucket() -> Bucket {
-    let config = environment::environment();
-
+async fn get_bucket(config: &S3Environment) -> Result<Bucket> {
     let region = Region::Custom {
-        region: config.s3.region.clone(),
-        endpoint: config.s3.url.clone(),
+        region: config.region.clone(),
+        endpoint: config.url.clone(),
     };
     let credentials = Credentials::new(
-        Some(&config.s3.access_key),
-        Some(&config.s3.secret_key),
+        Some(&config.access_key),
+        Some(&config.secret_key),
         None,
         None,
         None,
     )
-    .unwrap();
+    .context("Failed to construct S3 credentials!")?;
 
-    let bucket = Bucket::new(&config.s3.bucket_name, region.clone(), credentials.clone()).unwrap();
+    let bucket = Bucket::new(&config.bucket_name, region.clone(), credentials.clone())
+        .context("Failed to construct the S3 bucket!")?;
 
-    if let Err(error) = bucket.list("".to_string(), None).await {
-        let error_message = format!(
-            "Can't access storage bucket! No data at all will be stored. Please correct stored credentials. Details: {}",
-            error
-        );
-        logger().error(&error_message);
-        exit();
-    }
+    bucket.list("".to_string(), None).await.with_context(|| {
+        format!(
+            "Can't access storage bucket `{}` ({})! Backups can't be stored there. Please correct stored credentials.",
+            config.bucket_name, config.url
+        )
+    })?;
 
-    *bucket
+    Ok(*bucket)
 }
 
 impl S3Client {
-    pub async fn new() -> Self {
-        let bucket = get_main_bucket().await;
-        S3Client { bucket }
+    pub async fn new(config: &S3Environment) -> Result<Self> {
+        let bucket = get_bucket(config).await?;
+        Ok(S3Client { bucket })
+    }
+}
+
+#[async_trait]
+impl StorageBackend for S3Client {
+    async fn upload(
+        &self,
+        path: &str,
+        filename: &str,
+        data: &mut (dyn AsyncRead + Unpin + Send),
+    ) -> Result<()> {
+        // Large files are sent in multiple parts, as they are read.
+        let object_path = format!("{}/{}", path, filename);
+        let response = self
+            .bucket
+            .put_object_stream(data, &object_path)
+            .await
+            .context("Failed to upload the file to S3!")?;
+        if response.status_code() != 200 {
+            bail!(
+                "Failed to upload `{}` to S3! Server responded with {}.",
+                object_path,
+                response.status_code()
+            );
+        }
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>> {
+        let results = self
+            .bucket
+            .list(prefix.to_string(), None)
+            .await
+            .with_context(|| format!("Failed to list `{}` in S3!", prefix))?;
+
+        let objects = results
+            .into_iter()
+            .flat_map(|result| result.contents)
+            .map(|object| StoredObject {
+                path: object.key,
+                size: object.size,
+            })
+            .collect();
+        Ok(objects)
+    }
+
+    async fn download(&self, path: &str, data: &mut (dyn AsyncWrite + Unpin + Send)) -> Result<()> {
+        let status_code = self
+            .bucket
+            .get_object_to_writer(path, data)
+            .await
+            .with_context(|| format!("Failed to download `{}` from S3!", path))?;
+        if status_code != 200 {
+            bail!(
+                "Failed to download `{}` from S3! Server responded with {}.",
+                path,
+                status_code
+            );
+        }
+
+        Ok(())
     }
 
-    pub async fn upload(&self, app_name: &str, filename: &str, data: Vec<u8>) {
-        let object_path = format!("{}/{}", app_name, filename);
+    async fn delete(&self, path: &str) -> Result<()> {
         self.bucket
-            .put_object(object_path, &data)
+            .delete_object(path)
             .await
-            .expect("Failed to upload the file to S3!");
+            .with_context(|| format!("Failed to delete `{}` from S3!", path))?;
+        Ok(())
     }
 }
@@ -0,0 +1,182 @@
+use super::BackendListing;
+use crate::api_wrappers::database::types::BackupRecord;
+use crate::utils::time::format_path_friendly_date;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CatalogueEntryState {
+    // Known to both the storage and the database.
+    Consistent,
+    // Recorded in the database, but not found in any storage backend.
+    MissingInStorage,
+    // Found in storage, but not recorded in the database.
+    MissingInDatabase,
+}
+
+impl CatalogueEntryState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Consistent => "ok",
+            Self::MissingInStorage => "missing in storage",
+            Self::MissingInDatabase => "missing in database",
+        }
+    }
+}
+
+// A single backup, as seen by both the storage backends and the database.
+#[derive(Clone, Debug)]
+pub struct CatalogueEntry {
+    pub archiver: String,
+    pub directory: String,
+    pub timestamp: String,
+    // Size of the stored files if they were found, otherwise the recorded one.
+    pub size: u64,
+    pub content_hash: Option<String>,
+    // Names of storage backends holding the backup.
+    pub destinations: Vec<String>,
+    pub state: CatalogueEntryState,
+}
+
+// Matches backups of a single archiver found in storage with the ones recorded in the database.
+// Entries are sorted from the newest.
+pub fn build_catalogue(
+    archiver: &str,
+    listings: &[BackendListing],
+    records: &[BackupRecord],
+) -> Vec<CatalogueEntry> {
+    let mut entries: Vec<CatalogueEntry> = Vec::new();
+
+    for listing in listings {
+        for backup in &listing.backups {
+            if let Some(entry) = entries
+                .iter_mut()
+                .find(|entry| entry.directory == backup.directory)
+            {
+                entry.destinations.push(listing.backend_name.clone());
+                continue;
+            }
+
+            let record = records
+                .iter()
+                .find(|record| record.path == backup.directory);
+            entries.push(CatalogueEntry {
+                archiver: archiver.to_string(),
+                directory: backup.directory.clone(),
+                timestamp: format_path_friendly_date(&backup.created_at),
+                size: backup.objects.iter().map(|object| object.size).sum(),
+                content_hash: record.map(|record| record.content_hash.clone()),
+                destinations: vec![listing.backend_name.clone()],
+                state: match record {
+                    Some(_) => CatalogueEntryState::Consistent,
+                    None => CatalogueEntryState::MissingInDatabase,
+                },
+            });
+        }
+    }
+
+    for record in records {
+        if entries.iter().any(|entry| entry.directory == record.path) {
+            continue;
+        }
+
+        let timestamp = record.path.rsplit('/').next().unwrap_or_default();
+        entries.push(CatalogueEntry {
+            archiver: archiver.to_string(),
+            directory: record.path.clone(),
+            timestamp: timestamp.to_string(),
+            size: record.size,
+            content_hash: Some(record.content_hash.clone()),
+            destinations: Vec::new(),
+            state: CatalogueEntryState::MissingInStorage,
+        });
+    }
+
+    // Timestamps are zero-padded, so they can be sorted as strings.
+    entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    mod build_catalogue {
+        use super::super::*;
+        use crate::api_wrappers::database::types::BackupStatus;
+        use crate::api_wrappers::storage::retention::group_objects_into_backups;
+        use crate::api_wrappers::storage::types::StoredObject;
+        use crate::utils::clock::{Clock, FakeClock};
+
+        fn get_listing(backend_name: &str, timestamps: &[&str]) -> BackendListing {
+            let objects = timestamps
+                .iter()
+                .map(|timestamp| StoredObject {
+                    path: format!("tests/{}/backup.zip", timestamp),
+                    size: 10,
+                })
+                .collect();
+            BackendListing {
+                backend_name: backend_name.to_string(),
+                backups: group_objects_into_backups("tests/", objects),
+            }
+        }
+
+        fn get_record(timestamp: &str) -> BackupRecord {
+            BackupRecord {
+                app_name: "tests".to_string(),
+                path: format!("tests/{}", timestamp),
+                content_hash: format!("hash_{}", timestamp),
+                size: 5,
+                status: BackupStatus::Stored,
+                created_at: FakeClock::default().now(),
+            }
+        }
+
+        #[test]
+        fn matches_storage_with_database() {
+            let listings = vec![
+                get_listing("s3", &["2025-01-01T00:00:00", "2025-01-02T00:00:00"]),
+                get_listing("local", &["2025-01-01T00:00:00"]),
+            ];
+            let records = vec![
+                get_record("2025-01-01T00:00:00"),
+                get_record("2025-01-03T00:00:00"),
+            ];
+
+            let catalogue = build_catalogue("tests", &listings, &records);
+            let summary: Vec<(&str, Vec<String>, CatalogueEntryState)> = catalogue
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.timestamp.as_str(),
+                        entry.destinations.clone(),
+                        entry.state.clone(),
+                    )
+                })
+                .collect();
+
+            assert_eq!(
+                summary,
+                vec![
+                    (
+                        "2025-01-03T00:00:00",
+                        vec![],
+                        CatalogueEntryState::MissingInStorage
+                    ),
+                    (
+                        "2025-01-02T00:00:00",
+                        vec!["s3".to_string()],
+                        CatalogueEntryState::MissingInDatabase
+                    ),
+                    (
+                        "2025-01-01T00:00:00",
+                        vec!["s3".to_string(), "local".to_string()],
+                        CatalogueEntryState::Consistent
+                    ),
+                ]
+            );
+            assert_eq!(
+                catalogue[2].content_hash,
+                Some("hash_2025-01-01T00:00:00".to_string())
+            );
+            assert_eq!(catalogue[2].size, 10);
+        }
+    }
+}
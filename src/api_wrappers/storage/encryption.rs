@@ -0,0 +1,75 @@
+use super::types::BackupPayload;
+use crate::environment::environment;
+use crate::utils::encryption::{EncryptionManager, encrypt_stream, read_public_key_from_path};
+use anyhow::{Context, Result};
+use secrecy::ExposeSecret;
+use std::io::{BufReader, BufWriter, Read};
+
+pub static BACKUP_FILENAME: &str = "backup.zip";
+pub static ENCRYPTED_BACKUP_FILENAME: &str = "backup.zip.gpg";
+
+pub fn is_encrypted_backup(path: &str) -> bool {
+    path.ends_with(".gpg")
+}
+
+// Name under which new backups are stored, depending on whether they get encrypted.
+pub fn get_backup_filename() -> &'static str {
+    if environment().backup_encryption.enable {
+        ENCRYPTED_BACKUP_FILENAME
+    } else {
+        BACKUP_FILENAME
+    }
+}
+
+async fn get_encryption_manager() -> Result<EncryptionManager> {
+    let config = environment();
+    let key = config.secrets.decryption_key.clone().context(
+        "`SECRETS_DECRYPTION_KEY` is not defined, even though it's needed for backup encryption!",
+    )?;
+    let key_passphrase = config
+        .secrets
+        .decryption_key_passphrase
+        .as_ref()
+        .map(|passphrase| passphrase.expose_secret().to_string())
+        .unwrap_or_default();
+
+    EncryptionManager::try_new(key, key_passphrase)
+        .await
+        .context("Failed to load `SECRETS_DECRYPTION_KEY` for backup encryption!")
+}
+
+// Encrypts the backup to all keys listed in `BACKUP_ENCRYPTION_PUBLIC_KEYS`, or to the public key
+// of `SECRETS_DECRYPTION_KEY` if none are listed. The result is streamed into a temporary file, so
+// that the backup isn't held in memory twice.
+pub async fn encrypt_backup(mut data: impl Read + Send + 'static) -> Result<BackupPayload> {
+    let config = environment();
+
+    let mut public_keys = Vec::new();
+    for path in &config.backup_encryption.public_keys_paths {
+        public_keys.push(read_public_key_from_path(path).await?);
+    }
+    if public_keys.is_empty() {
+        public_keys.push(get_encryption_manager().await?.get_public_key());
+    }
+
+    let (payload, file) = BackupPayload::create_temporary_file("gpg")?;
+    tokio::task::spawn_blocking(move || {
+        encrypt_stream(&public_keys, &mut data, BufWriter::new(file))
+    })
+    .await??;
+    Ok(payload)
+}
+
+// Decrypts a backup using `SECRETS_DECRYPTION_KEY`, which has to match one of the public keys it
+// was encrypted to. Like encryption, it's streamed into a temporary file.
+pub async fn decrypt_backup(encrypted: BackupPayload) -> Result<BackupPayload> {
+    let encryption_manager = get_encryption_manager().await?;
+
+    let (payload, file) = BackupPayload::create_temporary_file("decrypted")?;
+    tokio::task::spawn_blocking(move || {
+        let mut reader = BufReader::new(encrypted.open_blocking()?);
+        encryption_manager.decrypt_stream(&mut reader, BufWriter::new(file))
+    })
+    .await??;
+    Ok(payload)
+}
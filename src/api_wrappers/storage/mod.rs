@@ -0,0 +1,160 @@
+pub mod catalogue;
+pub mod encryption;
+pub mod retention;
+pub mod types;
+pub mod utils;
+pub mod verification;
+
+use crate::api_wrappers::APIWrapper;
+use crate::api_wrappers::local::LocalStorageClient;
+use crate::api_wrappers::s3::S3Client;
+use crate::environment;
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use retention::{StoredBackup, group_objects_into_backups};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use types::{BackupPayload, StoredObject};
+
+// Place where the backups are stored.
+#[async_trait]
+pub trait StorageBackend: APIWrapper + Send + Sync {
+    // `path` is the directory of a single backup, e.g. `letterboxd/2025-01-01T00:00:00`. The data
+    // is read until its end, so that large backups don't have to be held in memory.
+    async fn upload(
+        &self,
+        path: &str,
+        filename: &str,
+        data: &mut (dyn AsyncRead + Unpin + Send),
+    ) -> Result<()>;
+
+    // Lists all files whose path starts with `prefix`, e.g. `letterboxd/`.
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>>;
+
+    // `path` is the full path of a single file, as returned by `list`. Like with `upload`, the data
+    // is written as it's received.
+    async fn download(&self, path: &str, data: &mut (dyn AsyncWrite + Unpin + Send)) -> Result<()>;
+
+    async fn delete(&self, path: &str) -> Result<()>;
+}
+
+static S3_REPLICA_PREFIX: &str = "s3-replica:";
+
+// Names of backends listed in the `STORAGE_BACKEND` environment variable, followed by the S3
+// replicas listed in `S3_REPLICAS`, e.g. `s3-replica:offsite`.
+pub fn get_storage_backend_names() -> Vec<String> {
+    let config = environment::environment();
+    let mut names = config.storage.backends.clone();
+    for replica in &config.s3_replicas {
+        names.push(format!("{}{}", S3_REPLICA_PREFIX, replica.name));
+    }
+    names
+}
+
+pub async fn get_storage_backend(name: &str) -> Result<Box<dyn StorageBackend>> {
+    let config = environment::environment();
+
+    if let Some(replica_name) = name.strip_prefix(S3_REPLICA_PREFIX) {
+        let Some(replica) = config
+            .s3_replicas
+            .iter()
+            .find(|replica| replica.name == replica_name)
+        else {
+            bail!("There's no `{}` S3 replica!", replica_name);
+        };
+        return Ok(Box::new(S3Client::new(&replica.s3).await?));
+    }
+
+    let backend: Box<dyn StorageBackend> = match name {
+        "s3" => Box::new(S3Client::new(&config.s3).await?),
+        "local" => Box::new(LocalStorageClient::new()),
+        other => bail!(
+            "Unknown storage backend `{}`! Supported ones are `s3` and `local`.",
+            other
+        ),
+    };
+    Ok(backend)
+}
+
+#[derive(Debug, Default)]
+pub struct ReplicationReport {
+    pub succeeded: Vec<String>,
+    pub failed: Vec<(String, anyhow::Error)>,
+}
+
+impl ReplicationReport {
+    pub fn get_failed_names(&self) -> Vec<String> {
+        self.failed.iter().map(|(name, _)| name.clone()).collect()
+    }
+}
+
+// Uploads the same file to every configured storage backend, including all S3 replicas. A failure
+// of one of them doesn't prevent uploading to the others, it's only recorded in the returned
+// report.
+pub async fn upload_to_all_storage_backends(
+    path: &str,
+    filename: &str,
+    payload: &BackupPayload,
+) -> ReplicationReport {
+    let mut report = ReplicationReport::default();
+
+    for name in get_storage_backend_names() {
+        let result = match get_storage_backend(&name).await {
+            Ok(backend) => match payload.open().await {
+                Ok(mut data) => backend.upload(path, filename, &mut data).await,
+                Err(error) => Err(error),
+            },
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok(()) => report.succeeded.push(name),
+            Err(error) => report.failed.push((name, error)),
+        }
+    }
+
+    report
+}
+
+// Downloads a single file into a temporary one, so that large backups aren't held in memory.
+pub async fn download_to_temporary_file(
+    backend: &dyn StorageBackend,
+    path: &str,
+) -> Result<BackupPayload> {
+    let (payload, file) = BackupPayload::create_temporary_file("download")?;
+    let mut file = tokio::fs::File::from_std(file);
+    backend.download(path, &mut file).await?;
+    file.flush().await?;
+    Ok(payload)
+}
+
+// Backups found under a single prefix in a single storage backend.
+pub struct BackendListing {
+    pub backend_name: String,
+    pub backups: Vec<StoredBackup>,
+}
+
+// Lists backups under `prefix` in every configured storage backend. Backends that couldn't be
+// listed are returned separately, so that the others can still be used.
+pub async fn list_backups_in_all_storage_backends(
+    prefix: &str,
+) -> (Vec<BackendListing>, Vec<(String, anyhow::Error)>) {
+    let mut listings = Vec::new();
+    let mut failed = Vec::new();
+
+    for name in get_storage_backend_names() {
+        let result = match get_storage_backend(&name).await {
+            Ok(backend) => backend.list(prefix).await,
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok(objects) => listings.push(BackendListing {
+                backend_name: name,
+                backups: group_objects_into_backups(prefix, objects),
+            }),
+            Err(error) => failed.push((name, error)),
+        }
+    }
+
+    (listings, failed)
+}
@@ -0,0 +1,226 @@
+use super::StorageBackend;
+use super::types::StoredObject;
+use crate::environment::types::RetentionPolicy;
+use crate::utils::time::parse_path_friendly_formatted_date;
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use std::collections::HashSet;
+
+// All files of a single backup, i.e. everything in a `<prefix><timestamp>/` directory.
+#[derive(Clone, Debug)]
+pub struct StoredBackup {
+    pub directory: String,
+    pub created_at: NaiveDateTime,
+    pub objects: Vec<StoredObject>,
+}
+
+// Groups objects listed under `prefix` by the backup they belong to, newest backups first.
+// Objects that aren't placed in a timestamped directory aren't backups, so they are left out.
+pub fn group_objects_into_backups(prefix: &str, objects: Vec<StoredObject>) -> Vec<StoredBackup> {
+    let mut backups: Vec<StoredBackup> = Vec::new();
+
+    for object in objects {
+        let Some(relative_path) = object.path.strip_prefix(prefix) else {
+            continue;
+        };
+        let Some((timestamp, _)) = relative_path.split_once('/') else {
+            continue;
+        };
+        let Some(created_at) = parse_path_friendly_formatted_date(timestamp) else {
+            continue;
+        };
+
+        let directory = format!("{}{}", prefix, timestamp);
+        match backups
+            .iter_mut()
+            .find(|backup| backup.directory == directory)
+        {
+            Some(backup) => backup.objects.push(object),
+            None => backups.push(StoredBackup {
+                directory,
+                created_at,
+                objects: vec![object],
+            }),
+        }
+    }
+
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    backups
+}
+
+// Keeps the newest backup of each of the `amount` most recent periods, e.g. days.
+fn keep_newest_per_period(
+    backups: &[StoredBackup],
+    amount: u64,
+    get_period: impl Fn(&NaiveDateTime) -> String,
+    kept_directories: &mut HashSet<String>,
+) {
+    let mut periods = HashSet::new();
+    for backup in backups {
+        if periods.len() as u64 >= amount {
+            break;
+        }
+        if periods.insert(get_period(&backup.created_at)) {
+            kept_directories.insert(backup.directory.clone());
+        }
+    }
+}
+
+// Works out which backups aren't kept by any of the policy's rules. `backups` have to be sorted
+// from the newest, as returned by `group_objects_into_backups`.
+pub fn get_backups_to_prune(
+    backups: &[StoredBackup],
+    policy: &RetentionPolicy,
+) -> Vec<StoredBackup> {
+    if policy.keep_last == 0
+        && policy.keep_daily == 0
+        && policy.keep_weekly == 0
+        && policy.keep_monthly == 0
+    {
+        return Vec::new();
+    }
+
+    let mut kept_directories = HashSet::new();
+    for backup in backups.iter().take(policy.keep_last as usize) {
+        kept_directories.insert(backup.directory.clone());
+    }
+    keep_newest_per_period(
+        backups,
+        policy.keep_daily,
+        |date| date.format("%Y-%m-%d").to_string(),
+        &mut kept_directories,
+    );
+    keep_newest_per_period(
+        backups,
+        policy.keep_weekly,
+        |date| date.format("%G-W%V").to_string(),
+        &mut kept_directories,
+    );
+    keep_newest_per_period(
+        backups,
+        policy.keep_monthly,
+        |date| date.format("%Y-%m").to_string(),
+        &mut kept_directories,
+    );
+
+    backups
+        .iter()
+        .filter(|backup| !kept_directories.contains(&backup.directory))
+        .cloned()
+        .collect()
+}
+
+// Deletes backups under `prefix` that aren't kept by the policy, and returns them. In dry-run
+// mode, nothing is deleted, but the backups that would be are still returned.
+pub async fn prune_backups(
+    backend: &dyn StorageBackend,
+    prefix: &str,
+    policy: &RetentionPolicy,
+    is_dry_run: bool,
+) -> Result<Vec<StoredBackup>> {
+    let objects = backend.list(prefix).await?;
+    let backups = group_objects_into_backups(prefix, objects);
+    let backups_to_prune = get_backups_to_prune(&backups, policy);
+
+    if !is_dry_run {
+        for backup in &backups_to_prune {
+            for object in &backup.objects {
+                backend.delete(&object.path).await?;
+            }
+        }
+    }
+
+    Ok(backups_to_prune)
+}
+
+#[cfg(test)]
+mod tests {
+    mod get_backups_to_prune {
+        use super::super::*;
+
+        fn get_backups(timestamps: &[&str]) -> Vec<StoredBackup> {
+            let objects = timestamps
+                .iter()
+                .map(|timestamp| StoredObject {
+                    path: format!("tests/{}/backup.zip", timestamp),
+                    size: 0,
+                })
+                .collect();
+            group_objects_into_backups("tests/", objects)
+        }
+
+        fn get_pruned_directories(
+            backups: &[StoredBackup],
+            policy: RetentionPolicy,
+        ) -> Vec<String> {
+            get_backups_to_prune(backups, &policy)
+                .into_iter()
+                .map(|backup| backup.directory)
+                .collect()
+        }
+
+        #[test]
+        fn keeps_everything_by_default() {
+            let backups = get_backups(&["2025-01-01T00:00:00", "2025-01-02T00:00:00"]);
+            assert!(get_pruned_directories(&backups, RetentionPolicy::default()).is_empty());
+        }
+
+        #[test]
+        fn ignores_objects_outside_of_timestamped_directories() {
+            let objects = vec![
+                StoredObject {
+                    path: "tests/notes.txt".to_string(),
+                    size: 0,
+                },
+                StoredObject {
+                    path: "tests/account/2025-01-01T00:00:00/backup.zip".to_string(),
+                    size: 0,
+                },
+            ];
+            assert!(group_objects_into_backups("tests/", objects).is_empty());
+        }
+
+        #[test]
+        fn keeps_last_backups() {
+            let backups = get_backups(&[
+                "2025-01-01T00:00:00",
+                "2025-01-03T00:00:00",
+                "2025-01-02T00:00:00",
+            ]);
+            let policy = RetentionPolicy {
+                keep_last: 2,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                get_pruned_directories(&backups, policy),
+                vec!["tests/2025-01-01T00:00:00"]
+            );
+        }
+
+        #[test]
+        fn keeps_newest_backup_of_each_period() {
+            let backups = get_backups(&[
+                // Two backups on the same day, in the same week.
+                "2025-01-06T10:00:00",
+                "2025-01-06T20:00:00",
+                // Previous week.
+                "2025-01-04T00:00:00",
+                // Previous month.
+                "2024-12-20T00:00:00",
+                "2024-12-01T00:00:00",
+            ]);
+            let policy = RetentionPolicy {
+                keep_daily: 1,
+                keep_weekly: 2,
+                keep_monthly: 2,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                get_pruned_directories(&backups, policy),
+                vec!["tests/2025-01-06T10:00:00", "tests/2024-12-01T00:00:00"]
+            );
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::utils::uuid;
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::PathBuf;
+use tokio::io::AsyncRead;
+
+// A single file kept by a storage backend.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredObject {
+    // Full path, e.g. `letterboxd/2025-01-01T00:00:00/backup.zip`.
+    pub path: String,
+    pub size: u64,
+}
+
+// Data of a backup that's about to be uploaded, or has just been downloaded. Encrypted and
+// downloaded backups are written to a temporary file, which is removed once the payload is dropped,
+// so that they aren't held in memory.
+pub enum BackupPayload {
+    Raw(Vec<u8>),
+    File(PathBuf),
+}
+
+// Reader returned by `open_blocking`. Zip archives can only be read with seeking.
+pub trait BlockingReader: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> BlockingReader for T {}
+
+impl BackupPayload {
+    // The payload is created first, so that the file gets removed even if writing to it fails.
+    pub fn create_temporary_file(extension: &str) -> Result<(Self, File)> {
+        let path = std::env::temp_dir().join(format!("asmda_{}.{}", uuid::get_random(), extension));
+        let file =
+            File::create(&path).with_context(|| format!("Failed to create {}!", path.display()))?;
+        Ok((Self::File(path), file))
+    }
+
+    // Each reader starts from the beginning, so that the payload can be uploaded multiple times.
+    pub async fn open(&self) -> Result<Box<dyn AsyncRead + Unpin + Send + '_>> {
+        match self {
+            Self::Raw(data) => Ok(Box::new(data.as_slice())),
+            Self::File(path) => {
+                let file = tokio::fs::File::open(path)
+                    .await
+                    .with_context(|| format!("Failed to open {}!", path.display()))?;
+                Ok(Box::new(file))
+            }
+        }
+    }
+
+    // Same as `open`, but for blocking threads.
+    pub fn open_blocking(&self) -> Result<Box<dyn BlockingReader + '_>> {
+        match self {
+            Self::Raw(data) => Ok(Box::new(std::io::Cursor::new(data.as_slice()))),
+            Self::File(path) => {
+                let file = File::open(path)
+                    .with_context(|| format!("Failed to open {}!", path.display()))?;
+                Ok(Box::new(file))
+            }
+        }
+    }
+}
+
+impl Drop for BackupPayload {
+    fn drop(&mut self) {
+        if let Self::File(path) = self {
+            std::fs::remove_file(path).ok();
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::archivers::Archiver;
+use crate::utils::clock::Clock;
+use crate::utils::time::get_current_path_friendly_formatted_date;
+
+// Directory containing all backups of the archiver.
+pub fn get_backup_prefix_for_archiver<T: Archiver>(archiver: &T) -> String {
+    format!("{}/", &archiver.get_identificator().as_str())
+}
+
+pub fn get_backup_path_for_archiver<T: Archiver>(archiver: &T, clock: &dyn Clock) -> String {
+    format!(
+        "{}{}",
+        get_backup_prefix_for_archiver(archiver),
+        get_current_path_friendly_formatted_date(clock)
+    )
+}
+
+// Formats an amount of bytes in a human readable way, e.g. `1.5 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit_index = 0;
+    while size >= 1024.0 && unit_index < units.len() - 1 {
+        size /= 1024.0;
+        unit_index += 1;
+    }
+
+    if unit_index == 0 {
+        return format!("{} B", bytes);
+    }
+    format!("{:.1} {}", size, units[unit_index])
+}
+
+#[cfg(test)]
+mod tests {
+    mod format_size {
+        use super::super::*;
+
+        #[test]
+        fn formats_correctly() {
+            assert_eq!(format_size(512), "512 B");
+            assert_eq!(format_size(1536), "1.5 KiB");
+            assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+        }
+    }
+}
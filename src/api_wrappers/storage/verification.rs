@@ -0,0 +1,251 @@
+use super::catalogue::{CatalogueEntryState, build_catalogue};
+use super::encryption::{decrypt_backup, is_encrypted_backup};
+use super::{
+    download_to_temporary_file, get_storage_backend, list_backups_in_all_storage_backends,
+};
+use crate::api_wrappers::database::types::BackupRecord;
+use crate::utils::hashing::read_content_hash;
+use anyhow::{Context, Result, bail};
+use std::io::{self, Read, Seek};
+use zip::ZipArchive;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VerificationStatus {
+    // Holds the amount of entries in the archive, or 0 if the backup isn't one.
+    Valid(usize),
+    // Recorded in the database, but not found in any storage backend.
+    Missing,
+    Corrupted(String),
+}
+
+#[derive(Debug)]
+pub struct VerificationResult {
+    pub directory: String,
+    // `None` if the backup hasn't been found in any storage backend.
+    pub backend_name: Option<String>,
+    pub status: VerificationStatus,
+}
+
+#[derive(Debug, Default)]
+pub struct VerificationReport {
+    pub results: Vec<VerificationResult>,
+    // Backends that couldn't be listed, so their backups haven't been verified.
+    pub failed_listings: Vec<(String, anyhow::Error)>,
+}
+
+impl VerificationReport {
+    pub fn get_amount_of_problems(&self) -> usize {
+        let amount_of_invalid_backups = self
+            .results
+            .iter()
+            .filter(|result| !matches!(result.status, VerificationStatus::Valid(_)))
+            .count();
+        amount_of_invalid_backups + self.failed_listings.len()
+    }
+
+    // Human readable descriptions of every problem found.
+    pub fn get_problems_descriptions(&self) -> Vec<String> {
+        let mut descriptions: Vec<String> = self
+            .failed_listings
+            .iter()
+            .map(|(name, error)| {
+                format!("Failed to list backups in `{}`! Details: {:#}", name, error)
+            })
+            .collect();
+
+        for result in &self.results {
+            match (&result.status, &result.backend_name) {
+                (VerificationStatus::Valid(_), _) => {}
+                (VerificationStatus::Missing, _) => descriptions.push(format!(
+                    "`{}` is missing from all storage backends!",
+                    result.directory
+                )),
+                (VerificationStatus::Corrupted(reason), Some(backend_name)) => {
+                    descriptions.push(format!(
+                        "`{}` in `{}` is corrupted! Details: {}",
+                        result.directory, backend_name, reason
+                    ))
+                }
+                (VerificationStatus::Corrupted(reason), None) => descriptions.push(format!(
+                    "`{}` is corrupted! Details: {}",
+                    result.directory, reason
+                )),
+            }
+        }
+
+        descriptions
+    }
+}
+
+// Reads every entry of the archive, which makes the zip library check their CRC32 checksums.
+// Returns the amount of entries.
+fn check_zip_archive(data: impl Read + Seek) -> Result<usize> {
+    let mut archive = ZipArchive::new(data).context("Can't open the zip archive")?;
+
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .with_context(|| format!("Can't open entry #{} of the zip archive", index))?;
+        let name = entry.name().to_string();
+        io::copy(&mut entry, &mut io::sink())
+            .with_context(|| format!("Can't read `{}` from the zip archive", name))?;
+    }
+
+    Ok(archive.len())
+}
+
+// Checks already downloaded (and decrypted) backup data. If the hash recorded at the time of
+// uploading is known, it has to match.
+pub fn check_backup_data(
+    mut data: impl Read + Seek,
+    is_zip: bool,
+    expected_hash: Option<&str>,
+) -> Result<usize> {
+    let amount_of_entries = if is_zip {
+        check_zip_archive(&mut data)?
+    } else {
+        0
+    };
+
+    if let Some(expected_hash) = expected_hash {
+        data.rewind()?;
+        let actual_hash = read_content_hash(data).context("Can't read the backup")?;
+        if actual_hash != expected_hash {
+            bail!(
+                "Content hash doesn't match the recorded one (expected {}, got {})",
+                expected_hash,
+                actual_hash
+            );
+        }
+    }
+
+    Ok(amount_of_entries)
+}
+
+async fn verify_stored_file(
+    backend_name: &str,
+    path: &str,
+    expected_hash: Option<&str>,
+) -> Result<usize> {
+    let backend = get_storage_backend(backend_name).await?;
+    let mut payload = download_to_temporary_file(backend.as_ref(), path).await?;
+    if is_encrypted_backup(path) {
+        payload = decrypt_backup(payload).await?;
+    }
+
+    let is_zip = path.trim_end_matches(".gpg").ends_with(".zip");
+    let expected_hash = expected_hash.map(String::from);
+    tokio::task::spawn_blocking(move || {
+        check_backup_data(payload.open_blocking()?, is_zip, expected_hash.as_deref())
+    })
+    .await?
+}
+
+// Downloads every backup of the archiver from every storage backend holding it, and checks it.
+// `records` are all backups stored according to the database.
+pub async fn verify_backups(archiver_name: &str, records: &[BackupRecord]) -> VerificationReport {
+    let prefix = format!("{}/", archiver_name);
+    let (listings, failed_listings) = list_backups_in_all_storage_backends(&prefix).await;
+    let records: Vec<_> = records
+        .iter()
+        .filter(|record| record.app_name == archiver_name)
+        .cloned()
+        .collect();
+
+    let mut report = VerificationReport {
+        results: Vec::new(),
+        failed_listings,
+    };
+    for entry in build_catalogue(archiver_name, &listings, &records) {
+        if entry.state == CatalogueEntryState::MissingInStorage {
+            report.results.push(VerificationResult {
+                directory: entry.directory,
+                backend_name: None,
+                status: VerificationStatus::Missing,
+            });
+            continue;
+        }
+
+        for listing in &listings {
+            let Some(backup) = listing
+                .backups
+                .iter()
+                .find(|backup| backup.directory == entry.directory)
+            else {
+                continue;
+            };
+
+            for object in &backup.objects {
+                let status = match verify_stored_file(
+                    &listing.backend_name,
+                    &object.path,
+                    entry.content_hash.as_deref(),
+                )
+                .await
+                {
+                    Ok(amount_of_entries) => VerificationStatus::Valid(amount_of_entries),
+                    Err(error) => VerificationStatus::Corrupted(format!("{:#}", error)),
+                };
+                report.results.push(VerificationResult {
+                    directory: entry.directory.clone(),
+                    backend_name: Some(listing.backend_name.clone()),
+                    status,
+                });
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    mod check_backup_data {
+        use super::super::*;
+        use crate::utils::hashing::get_content_hash;
+        use std::io::{Cursor, Write};
+        use zip::CompressionMethod;
+        use zip::write::{SimpleFileOptions, ZipWriter};
+
+        // Entries aren't compressed, so that their contents can be found and damaged in tests.
+        fn create_zip() -> Vec<u8> {
+            let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+            let options =
+                SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+            for name in ["a.csv", "b.csv"] {
+                writer.start_file(name, options).unwrap();
+                writer.write_all(b"Komm, susser Tod").unwrap();
+            }
+            writer.finish().unwrap().into_inner()
+        }
+
+        #[test]
+        fn accepts_valid_archives() {
+            let zip = create_zip();
+            let hash = get_content_hash(&zip);
+
+            assert_eq!(
+                check_backup_data(Cursor::new(&zip), true, Some(&hash)).unwrap(),
+                2
+            );
+        }
+
+        #[test]
+        fn rejects_mismatched_hashes() {
+            let zip = create_zip();
+
+            assert!(check_backup_data(Cursor::new(&zip), true, Some("0000")).is_err());
+        }
+
+        #[test]
+        fn rejects_damaged_archives() {
+            let mut zip = create_zip();
+            // Damages the contents of the first entry, which breaks its checksum.
+            let contents_index = zip.windows(4).position(|window| window == b"Komm").unwrap();
+            zip[contents_index] = b'X';
+
+            assert!(check_backup_data(Cursor::new(&zip), true, None).is_err());
+            assert!(check_backup_data(Cursor::new(&zip[..zip.len() / 2]), true, None).is_err());
+        }
+    }
+}
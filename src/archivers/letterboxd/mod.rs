@@ -1,20 +1,29 @@
 use crate::api_wrappers::browser::letterboxd::LetterboxdBrowserAPIWrapper;
 use crate::archivers::{Archiver, InstantArchiver};
+use crate::environment;
+use crate::environment::types::{LetterboxdAccount, RetentionPolicy};
 use crate::utils::constants::ArchiverIdentificator;
 use anyhow::Result;
 
-pub struct LetterboxdArchiver;
+pub struct LetterboxdArchiver {
+    pub account: LetterboxdAccount,
+}
 
 impl Archiver for LetterboxdArchiver {
     fn get_identificator(&self) -> ArchiverIdentificator {
-        ArchiverIdentificator::Letterboxd
+        ArchiverIdentificator::Letterboxd(self.account.name.clone())
+    }
+
+    fn get_retention_policy(&self) -> RetentionPolicy {
+        let config = environment::environment();
+        config.letterboxd.retention.clone()
     }
 }
 
 impl InstantArchiver for LetterboxdArchiver {
     async fn get_data(&self) -> Result<Vec<u8>> {
         let lettterboxd_wrapper = LetterboxdBrowserAPIWrapper::new().await;
-        lettterboxd_wrapper.launch().await?;
+        lettterboxd_wrapper.launch(&self.account).await?;
         let data = lettterboxd_wrapper.export_data().await?;
         lettterboxd_wrapper.close().await;
 
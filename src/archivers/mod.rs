@@ -1,11 +1,19 @@
 pub mod letterboxd;
 
+use crate::api_wrappers::database::types::PendingRequest;
+use crate::api_wrappers::mail::types::MailRule;
+use crate::environment::types::RetentionPolicy;
 use crate::utils::constants::ArchiverIdentificator;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::future::Future;
 
 pub trait Archiver {
     fn get_identificator(&self) -> ArchiverIdentificator;
+
+    // Decides which of the stored backups get pruned. By default, all of them are kept.
+    fn get_retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy::default()
+    }
 }
 
 // For platforms that support instantly exporting data.
@@ -15,5 +23,38 @@ pub trait InstantArchiver: Archiver {
 
 // For platforms that require some time to setup the package to archive, and then for example send it via mail.
 pub trait RequestArchiver: Archiver {
-    fn request_data(&self);
+    // Asks the platform to start preparing the package.
+    fn request_data(&self) -> impl Future<Output = Result<()>> + Send;
+
+    // Tries to get the package prepared after `request_data` has been called. Should return
+    // `None` if the platform hasn't finished preparing it yet. Platforms that let us know about
+    // the package via mail can leave this as is, and define `get_mail_rule` instead.
+    fn collect_data(
+        &self,
+        _request: &PendingRequest,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>>> + Send {
+        async { Ok(None) }
+    }
+
+    // If defined, the IMAP inbox (when enabled) is searched for matching "export is ready"
+    // messages, and the download link found in them is passed to `download_data`.
+    fn get_mail_rule(&self) -> Option<MailRule> {
+        None
+    }
+
+    fn download_data(&self, url: &str) -> impl Future<Output = Result<Vec<u8>>> + Send {
+        async move {
+            let response = reqwest::get(url)
+                .await
+                .with_context(|| format!("Failed to download the package from {}!", url))?
+                .error_for_status()
+                .with_context(|| format!("Failed to download the package from {}!", url))?;
+            let bytes = response
+                .bytes()
+                .await
+                .context("Failed to read the downloaded package!")?;
+
+            Ok(bytes.to_vec())
+        }
+    }
 }
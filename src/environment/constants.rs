@@ -1,3 +1,4 @@
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 #[derive(PartialEq, Debug)]
@@ -25,6 +26,65 @@ pub enum EnvironmentVariable {
     StatusServerPort,
     WebDriverUrl,
     WebDriverPort,
+    ImapEnable,
+    ImapHost,
+    ImapPort,
+    ImapTls,
+    ImapUsername,
+    ImapPassword,
+    ImapMailbox,
+    StorageBackend,
+    LocalStoragePath,
+    SkipUnchangedBackups,
+    LetterboxdRetentionKeepLast,
+    LetterboxdRetentionKeepDaily,
+    LetterboxdRetentionKeepWeekly,
+    LetterboxdRetentionKeepMonthly,
+    RetentionDryRun,
+    BackupEncryptionEnable,
+    BackupEncryptionPublicKeys,
+    VerificationEnable,
+    VerificationFrequency,
+    ScheduleTimezone,
+    TaskRetryMaxAttempts,
+    TaskRetryInitialDelay,
+    TaskRetryMaxDelay,
+    TaskRetryJitter,
+    LetterboxdBackupTimeout,
+    VerificationTimeout,
+    SchedulerMaxConcurrentTasks,
+    LetterboxdBackupCatchUp,
+    VerificationCatchUp,
+    ConfigFile,
+    LetterboxdAccounts,
+    // Defined for each of the `LETTERBOXD_ACCOUNTS`, e.g. `LETTERBOXD_ALICE_USERNAME`.
+    LetterboxdAccountUsername(String),
+    LetterboxdAccountPassword(String),
+    S3Replicas,
+    // Defined for each of the `S3_REPLICAS`, e.g. `S3_REPLICA_OFFSITE_URL`.
+    S3ReplicaRegion(String),
+    S3ReplicaUrl(String),
+    S3ReplicaBucketName(String),
+    S3ReplicaAccessKey(String),
+    S3ReplicaSecretKey(String),
+}
+
+// Part of the names of environment variables defined for each account or S3 replica, e.g. `ALICE`
+// for `alice`.
+pub fn get_variable_name_part(name: &str) -> String {
+    name.to_uppercase().replace('-', "_")
+}
+
+// What values of an environment variable look like, see `utils::generic::validate_value`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueKind {
+    Text,
+    Boolean,
+    Integer,
+    List,
+    Schedule,
+    Timezone,
+    CatchUpPolicy,
 }
 
 impl EnvironmentVariable {
@@ -47,6 +107,59 @@ impl EnvironmentVariable {
             Self::StatusServerPort => "STATUS_SERVER_PORT",
             Self::WebDriverUrl => "WEBDRIVER_URL",
             Self::WebDriverPort => "WEBDRIVER_PORT",
+            Self::ImapEnable => "IMAP_ENABLE",
+            Self::ImapHost => "IMAP_HOST",
+            Self::ImapPort => "IMAP_PORT",
+            Self::ImapTls => "IMAP_TLS",
+            Self::ImapUsername => "IMAP_USERNAME",
+            Self::ImapPassword => "IMAP_PASSWORD",
+            Self::ImapMailbox => "IMAP_MAILBOX",
+            Self::StorageBackend => "STORAGE_BACKEND",
+            Self::LocalStoragePath => "LOCAL_STORAGE_PATH",
+            Self::SkipUnchangedBackups => "SKIP_UNCHANGED_BACKUPS",
+            Self::LetterboxdRetentionKeepLast => "LETTERBOXD_RETENTION_KEEP_LAST",
+            Self::LetterboxdRetentionKeepDaily => "LETTERBOXD_RETENTION_KEEP_DAILY",
+            Self::LetterboxdRetentionKeepWeekly => "LETTERBOXD_RETENTION_KEEP_WEEKLY",
+            Self::LetterboxdRetentionKeepMonthly => "LETTERBOXD_RETENTION_KEEP_MONTHLY",
+            Self::RetentionDryRun => "RETENTION_DRY_RUN",
+            Self::BackupEncryptionEnable => "BACKUP_ENCRYPTION_ENABLE",
+            Self::BackupEncryptionPublicKeys => "BACKUP_ENCRYPTION_PUBLIC_KEYS",
+            Self::VerificationEnable => "VERIFICATION_ENABLE",
+            Self::VerificationFrequency => "VERIFICATION_FREQUENCY",
+            Self::ScheduleTimezone => "SCHEDULE_TIMEZONE",
+            Self::TaskRetryMaxAttempts => "TASK_RETRY_MAX_ATTEMPTS",
+            Self::TaskRetryInitialDelay => "TASK_RETRY_INITIAL_DELAY",
+            Self::TaskRetryMaxDelay => "TASK_RETRY_MAX_DELAY",
+            Self::TaskRetryJitter => "TASK_RETRY_JITTER",
+            Self::LetterboxdBackupTimeout => "LETTERBOXD_BACKUP_TIMEOUT",
+            Self::VerificationTimeout => "VERIFICATION_TIMEOUT",
+            Self::SchedulerMaxConcurrentTasks => "SCHEDULER_MAX_CONCURRENT_TASKS",
+            Self::LetterboxdBackupCatchUp => "LETTERBOXD_BACKUP_CATCH_UP",
+            Self::VerificationCatchUp => "VERIFICATION_CATCH_UP",
+            Self::ConfigFile => "CONFIG_FILE",
+            Self::LetterboxdAccounts => "LETTERBOXD_ACCOUNTS",
+            Self::LetterboxdAccountUsername(account) => {
+                return format!("LETTERBOXD_{}_USERNAME", get_variable_name_part(account));
+            }
+            Self::LetterboxdAccountPassword(account) => {
+                return format!("LETTERBOXD_{}_PASSWORD", get_variable_name_part(account));
+            }
+            Self::S3Replicas => "S3_REPLICAS",
+            Self::S3ReplicaRegion(replica) => {
+                return format!("S3_REPLICA_{}_REGION", get_variable_name_part(replica));
+            }
+            Self::S3ReplicaUrl(replica) => {
+                return format!("S3_REPLICA_{}_URL", get_variable_name_part(replica));
+            }
+            Self::S3ReplicaBucketName(replica) => {
+                return format!("S3_REPLICA_{}_BUCKET_NAME", get_variable_name_part(replica));
+            }
+            Self::S3ReplicaAccessKey(replica) => {
+                return format!("S3_REPLICA_{}_ACCESS_KEY", get_variable_name_part(replica));
+            }
+            Self::S3ReplicaSecretKey(replica) => {
+                return format!("S3_REPLICA_{}_SECRET_KEY", get_variable_name_part(replica));
+            }
         };
         str.to_string()
     }
@@ -70,9 +183,57 @@ impl EnvironmentVariable {
             Self::StatusServerPort => false,
             Self::WebDriverUrl => false,
             Self::WebDriverPort => false,
+            Self::ImapEnable => false,
+            Self::ImapHost => false,
+            Self::ImapPort => false,
+            Self::ImapTls => false,
+            Self::ImapUsername => false,
+            Self::ImapPassword => true,
+            Self::ImapMailbox => false,
+            Self::StorageBackend => false,
+            Self::LocalStoragePath => false,
+            Self::SkipUnchangedBackups => false,
+            Self::LetterboxdRetentionKeepLast => false,
+            Self::LetterboxdRetentionKeepDaily => false,
+            Self::LetterboxdRetentionKeepWeekly => false,
+            Self::LetterboxdRetentionKeepMonthly => false,
+            Self::RetentionDryRun => false,
+            Self::BackupEncryptionEnable => false,
+            Self::BackupEncryptionPublicKeys => false,
+            Self::VerificationEnable => false,
+            Self::VerificationFrequency => false,
+            Self::ScheduleTimezone => false,
+            Self::TaskRetryMaxAttempts => false,
+            Self::TaskRetryInitialDelay => false,
+            Self::TaskRetryMaxDelay => false,
+            Self::TaskRetryJitter => false,
+            Self::LetterboxdBackupTimeout => false,
+            Self::VerificationTimeout => false,
+            Self::SchedulerMaxConcurrentTasks => false,
+            Self::LetterboxdBackupCatchUp => false,
+            Self::VerificationCatchUp => false,
+            Self::ConfigFile => false,
+            Self::LetterboxdAccounts => false,
+            Self::LetterboxdAccountUsername(_) => false,
+            Self::LetterboxdAccountPassword(_) => true,
+            Self::S3Replicas => false,
+            Self::S3ReplicaRegion(_) => false,
+            Self::S3ReplicaUrl(_) => false,
+            Self::S3ReplicaBucketName(_) => false,
+            Self::S3ReplicaAccessKey(_) => false,
+            Self::S3ReplicaSecretKey(_) => true,
         }
     }
 
+    // Whether they can also be read from files, see `utils::environment::find_secret_file_value`.
+    pub fn is_secret(&self) -> bool {
+        self.can_be_encrypted()
+            || matches!(
+                self,
+                Self::SecretsDecryptionKey | Self::SecretsDecryptionKeyPassphrase
+            )
+    }
+
     pub fn get_development_fallback_value(&self) -> Option<String> {
         let value = match self {
             Self::LetterboxdPassword => None,
@@ -91,6 +252,45 @@ impl EnvironmentVariable {
             Self::StatusServerPort => Some("3002"),
             Self::WebDriverUrl => Some("http://localhost"),
             Self::WebDriverPort => Some("4444"),
+            Self::ImapEnable => Some("false"),
+            Self::ImapHost => Some("localhost"),
+            Self::ImapPort => Some("993"),
+            Self::ImapTls => Some("true"),
+            Self::ImapUsername => None,
+            Self::ImapPassword => None,
+            Self::ImapMailbox => Some("INBOX"),
+            Self::StorageBackend => Some("s3"),
+            Self::LocalStoragePath => Some("./backups"),
+            Self::SkipUnchangedBackups => Some("true"),
+            Self::LetterboxdRetentionKeepLast => Some("0"),
+            Self::LetterboxdRetentionKeepDaily => Some("0"),
+            Self::LetterboxdRetentionKeepWeekly => Some("0"),
+            Self::LetterboxdRetentionKeepMonthly => Some("0"),
+            Self::RetentionDryRun => Some("false"),
+            Self::BackupEncryptionEnable => Some("false"),
+            Self::BackupEncryptionPublicKeys => Some(""),
+            Self::VerificationEnable => Some("false"),
+            Self::VerificationFrequency => Some("604800"),
+            Self::ScheduleTimezone => Some("local"),
+            Self::TaskRetryMaxAttempts => Some("3"),
+            Self::TaskRetryInitialDelay => Some("60"),
+            Self::TaskRetryMaxDelay => Some("3600"),
+            Self::TaskRetryJitter => Some("20"),
+            Self::LetterboxdBackupTimeout => Some("1800"),
+            Self::VerificationTimeout => Some("0"),
+            Self::SchedulerMaxConcurrentTasks => Some("2"),
+            Self::LetterboxdBackupCatchUp => Some("once"),
+            Self::VerificationCatchUp => Some("once"),
+            Self::ConfigFile => None,
+            Self::LetterboxdAccounts => Some(""),
+            Self::LetterboxdAccountUsername(_) => None,
+            Self::LetterboxdAccountPassword(_) => None,
+            Self::S3Replicas => Some(""),
+            Self::S3ReplicaRegion(_) => None,
+            Self::S3ReplicaUrl(_) => None,
+            Self::S3ReplicaBucketName(_) => None,
+            Self::S3ReplicaAccessKey(_) => None,
+            Self::S3ReplicaSecretKey(_) => None,
         };
         value.map(|value| value.to_string())
     }
@@ -104,7 +304,180 @@ impl EnvironmentVariable {
         #[allow(clippy::match_like_matches_macro)]
         match self {
             Self::SecretsDecryptionKey | Self::SecretsDecryptionKeyPassphrase => false,
+            // Only needed when `IMAP_ENABLE` is set to `"true"`.
+            Self::ImapUsername | Self::ImapPassword => false,
+            // Only needed for each of the `S3_REPLICAS`.
+            Self::S3ReplicaRegion(_)
+            | Self::S3ReplicaUrl(_)
+            | Self::S3ReplicaBucketName(_)
+            | Self::S3ReplicaAccessKey(_)
+            | Self::S3ReplicaSecretKey(_) => false,
+            // Without it, everything is configured with environment variables.
+            Self::ConfigFile => false,
+            // Only needed when `LETTERBOXD_ACCOUNTS` is empty, or for each of them respectively.
+            Self::LetterboxdUsername
+            | Self::LetterboxdPassword
+            | Self::LetterboxdAccountUsername(_)
+            | Self::LetterboxdAccountPassword(_) => false,
             _ => true,
         }
     }
+
+    // Dotted path of the key in the config file, mirroring the `Environment` sections. `None` if it
+    // can only be set with an environment variable.
+    pub fn get_config_key(&self) -> Option<String> {
+        let key = match self {
+            Self::LetterboxdPassword => "letterboxd.password",
+            Self::LetterboxdUsername => "letterboxd.username",
+            Self::LetterboxdBackupFrequency => "letterboxd.backup_schedule",
+            Self::LetterboxdBackupEnable => "letterboxd.backup_enable",
+            Self::S3Region => "s3.region",
+            Self::S3Url => "s3.url",
+            Self::S3BucketName => "s3.bucket_name",
+            Self::S3AccessKey => "s3.access_key",
+            Self::S3SecretKey => "s3.secret_key",
+            Self::SecretsAreEncrypted => "secrets.are_encrypted",
+            Self::SecretsDecryptionKey => "secrets.decryption_key",
+            Self::SecretsDecryptionKeyPassphrase => "secrets.decryption_key_passphrase",
+            Self::StatusServerEnable => "status_server.enable",
+            Self::StatusServerPort => "status_server.port",
+            Self::WebDriverUrl => "webdriver.url",
+            Self::WebDriverPort => "webdriver.port",
+            Self::ImapEnable => "imap.enable",
+            Self::ImapHost => "imap.host",
+            Self::ImapPort => "imap.port",
+            Self::ImapTls => "imap.tls",
+            Self::ImapUsername => "imap.username",
+            Self::ImapPassword => "imap.password",
+            Self::ImapMailbox => "imap.mailbox",
+            Self::StorageBackend => "storage.backends",
+            Self::LocalStoragePath => "storage.local_path",
+            Self::SkipUnchangedBackups => "storage.skip_unchanged",
+            Self::LetterboxdRetentionKeepLast => "letterboxd.retention.keep_last",
+            Self::LetterboxdRetentionKeepDaily => "letterboxd.retention.keep_daily",
+            Self::LetterboxdRetentionKeepWeekly => "letterboxd.retention.keep_weekly",
+            Self::LetterboxdRetentionKeepMonthly => "letterboxd.retention.keep_monthly",
+            Self::RetentionDryRun => "storage.retention_dry_run",
+            Self::BackupEncryptionEnable => "backup_encryption.enable",
+            Self::BackupEncryptionPublicKeys => "backup_encryption.public_keys_paths",
+            Self::VerificationEnable => "verification.enable",
+            Self::VerificationFrequency => "verification.schedule",
+            Self::ScheduleTimezone => "scheduler.timezone",
+            Self::TaskRetryMaxAttempts => "task_retry.max_attempts",
+            Self::TaskRetryInitialDelay => "task_retry.initial_delay_seconds",
+            Self::TaskRetryMaxDelay => "task_retry.max_delay_seconds",
+            Self::TaskRetryJitter => "task_retry.jitter_percentage",
+            Self::LetterboxdBackupTimeout => "letterboxd.backup_timeout",
+            Self::VerificationTimeout => "verification.timeout",
+            Self::SchedulerMaxConcurrentTasks => "scheduler.max_concurrent_tasks",
+            Self::LetterboxdBackupCatchUp => "letterboxd.backup_catch_up",
+            Self::VerificationCatchUp => "verification.catch_up",
+            Self::ConfigFile => return None,
+            // Names of the `letterboxd.accounts` tables.
+            Self::LetterboxdAccounts => return None,
+            Self::LetterboxdAccountUsername(account) => {
+                return Some(format!("letterboxd.accounts.{}.username", account));
+            }
+            Self::LetterboxdAccountPassword(account) => {
+                return Some(format!("letterboxd.accounts.{}.password", account));
+            }
+            // Names of the `s3_replicas` tables.
+            Self::S3Replicas => return None,
+            Self::S3ReplicaRegion(replica) => {
+                return Some(format!("s3_replicas.{}.region", replica));
+            }
+            Self::S3ReplicaUrl(replica) => {
+                return Some(format!("s3_replicas.{}.url", replica));
+            }
+            Self::S3ReplicaBucketName(replica) => {
+                return Some(format!("s3_replicas.{}.bucket_name", replica));
+            }
+            Self::S3ReplicaAccessKey(replica) => {
+                return Some(format!("s3_replicas.{}.access_key", replica));
+            }
+            Self::S3ReplicaSecretKey(replica) => {
+                return Some(format!("s3_replicas.{}.secret_key", replica));
+            }
+        };
+        Some(key.to_string())
+    }
+
+    pub fn from_config_key(key: &str) -> Option<Self> {
+        let segments: Vec<&str> = key.split('.').collect();
+        match segments.as_slice() {
+            ["letterboxd", "accounts", account, "username"] => {
+                Some(Self::LetterboxdAccountUsername(account.to_string()))
+            }
+            ["letterboxd", "accounts", account, "password"] => {
+                Some(Self::LetterboxdAccountPassword(account.to_string()))
+            }
+            ["s3_replicas", replica, field] => {
+                let replica = replica.to_string();
+                match *field {
+                    "region" => Some(Self::S3ReplicaRegion(replica)),
+                    "url" => Some(Self::S3ReplicaUrl(replica)),
+                    "bucket_name" => Some(Self::S3ReplicaBucketName(replica)),
+                    "access_key" => Some(Self::S3ReplicaAccessKey(replica)),
+                    "secret_key" => Some(Self::S3ReplicaSecretKey(replica)),
+                    _ => None,
+                }
+            }
+            _ => Self::iter().find(|variable| variable.get_config_key().as_deref() == Some(key)),
+        }
+    }
+
+    // Whether the key of the config file holds other keys, rather than a value.
+    pub fn is_config_section(key: &str) -> bool {
+        let segments: Vec<&str> = key.split('.').collect();
+        if let ["letterboxd", "accounts"]
+        | ["letterboxd", "accounts", _]
+        | ["s3_replicas"]
+        | ["s3_replicas", _] = segments.as_slice()
+        {
+            return true;
+        }
+
+        let prefix = format!("{}.", key);
+        Self::iter().any(|variable| {
+            variable
+                .get_config_key()
+                .is_some_and(|variable_key| variable_key.starts_with(&prefix))
+        })
+    }
+
+    pub fn get_value_kind(&self) -> ValueKind {
+        match self {
+            Self::LetterboxdBackupEnable
+            | Self::SecretsAreEncrypted
+            | Self::StatusServerEnable
+            | Self::ImapEnable
+            | Self::ImapTls
+            | Self::SkipUnchangedBackups
+            | Self::RetentionDryRun
+            | Self::BackupEncryptionEnable
+            | Self::VerificationEnable => ValueKind::Boolean,
+            Self::StatusServerPort
+            | Self::WebDriverPort
+            | Self::ImapPort
+            | Self::LetterboxdRetentionKeepLast
+            | Self::LetterboxdRetentionKeepDaily
+            | Self::LetterboxdRetentionKeepWeekly
+            | Self::LetterboxdRetentionKeepMonthly
+            | Self::TaskRetryMaxAttempts
+            | Self::TaskRetryInitialDelay
+            | Self::TaskRetryMaxDelay
+            | Self::TaskRetryJitter
+            | Self::LetterboxdBackupTimeout
+            | Self::VerificationTimeout
+            | Self::SchedulerMaxConcurrentTasks => ValueKind::Integer,
+            Self::StorageBackend
+            | Self::BackupEncryptionPublicKeys
+            | Self::LetterboxdAccounts
+            | Self::S3Replicas => ValueKind::List,
+            Self::LetterboxdBackupFrequency | Self::VerificationFrequency => ValueKind::Schedule,
+            Self::ScheduleTimezone => ValueKind::Timezone,
+            Self::LetterboxdBackupCatchUp | Self::VerificationCatchUp => ValueKind::CatchUpPolicy,
+            _ => ValueKind::Text,
+        }
+    }
 }
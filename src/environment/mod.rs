@@ -2,58 +2,220 @@ pub mod constants;
 pub mod types;
 pub mod utils;
 
+use anyhow::{Result, bail};
 use constants::EnvironmentVariable::*;
-use dotenv::dotenv;
-use once_cell::sync::OnceCell;
+use std::sync::{Arc, RwLock};
 use types::{
-    Environment, LetterboxdEnvironment, Metadata, S3Environment, SecretsEnvironment,
-    StatusServerEnvironment, WebDriverEnvironment,
+    BackupEncryptionEnvironment, Environment, ImapEnvironment, LetterboxdAccount,
+    LetterboxdEnvironment, Metadata, RetentionPolicy, RetryPolicy, S3Environment,
+    S3ReplicaEnvironment, SchedulerEnvironment, SecretsEnvironment, StatusServerEnvironment,
+    StorageEnvironment, VerificationEnvironment, WebDriverEnvironment,
 };
-use utils::decryption_key_passphrase::decryption_key_passphrase;
-use utils::environment::get_env_var;
+use utils::decryption_key_passphrase::{decryption_key_passphrase, disable_prompting};
+use utils::environment::{get_env_var, set_value_sources};
 use utils::generic::{
-    as_boolean, as_integer, get_database_path, get_logs_directory_path, get_program_version,
-    get_running_environment,
+    as_boolean, as_catch_up_policy, as_integer, as_list, as_task_schedule, find_colliding_names,
+    get_database_path, get_logs_directory_path, get_program_version, get_running_environment,
+    is_valid_name,
 };
+use utils::validation::{describe_problems, load_valid_value_sources};
 
-static ENVIRONMENT: OnceCell<Environment> = OnceCell::new();
-
-pub fn environment() -> &'static Environment {
-    ENVIRONMENT.get_or_init(|| {
-        dotenv().ok();
-        Environment {
-            metadata: Metadata {
-                database_path: get_database_path(),
-                running_environment: get_running_environment(),
-                logs_directory_path: get_logs_directory_path(),
-                program_version: get_program_version(),
-            },
-            letterboxd: LetterboxdEnvironment {
-                password: get_env_var(LetterboxdPassword),
-                username: get_env_var(LetterboxdUsername),
-                backup_frequency: as_integer(get_env_var(LetterboxdBackupFrequency)),
-                backup_enable: as_boolean(get_env_var(LetterboxdBackupEnable)),
-            },
-            s3: S3Environment {
-                region: get_env_var(S3Region),
-                url: get_env_var(S3Url),
-                bucket_name: get_env_var(S3BucketName),
-                access_key: get_env_var(S3AccessKey),
-                secret_key: get_env_var(S3SecretKey),
-            },
-            secrets: SecretsEnvironment {
-                are_encrypted: as_boolean(get_env_var(SecretsAreEncrypted)),
-                decryption_key: get_env_var(SecretsDecryptionKey),
-                decryption_key_passphrase: decryption_key_passphrase().clone(),
-            },
-            status_server: StatusServerEnvironment {
-                enable: as_boolean(get_env_var(StatusServerEnable)),
-                port: as_integer(get_env_var(StatusServerPort)),
-            },
-            webdriver: WebDriverEnvironment {
-                url: get_env_var(WebDriverUrl),
-                port: as_integer(get_env_var(WebDriverPort)),
-            },
-        }
+// A snapshot, swapped by `reload_environment`. Holders of the previous one keep using it.
+static ENVIRONMENT: RwLock<Option<Arc<Environment>>> = RwLock::new(None);
+
+fn get_required_env_var(variable: constants::EnvironmentVariable, reason: &str) -> String {
+    get_env_var::<Option<String>>(variable.clone()).unwrap_or_else(|| {
+        panic!(
+            "Environment variable {} not set! {}",
+            variable.as_str(),
+            reason
+        )
     })
 }
+
+fn check_names_dont_collide(names: &[String], kind: &str) {
+    if let Some((name, other_name)) = find_colliding_names(names).first() {
+        panic!(
+            "`{}` and `{}` are {} names sharing environment variables! Rename one of them.",
+            name, other_name, kind
+        );
+    }
+}
+
+fn get_letterboxd_accounts() -> Vec<LetterboxdAccount> {
+    let names = as_list(get_env_var(LetterboxdAccounts));
+    if names.is_empty() {
+        let reason = "Set it, or define `LETTERBOXD_ACCOUNTS`.";
+        return vec![LetterboxdAccount {
+            name: None,
+            username: get_required_env_var(LetterboxdUsername, reason),
+            password: get_required_env_var(LetterboxdPassword, reason),
+        }];
+    }
+    check_names_dont_collide(&names, "Letterboxd account");
+
+    names
+        .into_iter()
+        .map(|name| {
+            if !is_valid_name(&name) {
+                panic!(
+                    "`{}` is not a valid Letterboxd account name! Use only lowercase letters, digits, `-` and `_`.",
+                    name
+                );
+            }
+
+            let reason = format!("It's required for the `{}` Letterboxd account.", name);
+            LetterboxdAccount {
+                username: get_required_env_var(LetterboxdAccountUsername(name.clone()), &reason),
+                password: get_required_env_var(LetterboxdAccountPassword(name.clone()), &reason),
+                name: Some(name),
+            }
+        })
+        .collect()
+}
+
+fn get_s3_replicas() -> Vec<S3ReplicaEnvironment> {
+    let names = as_list(get_env_var(S3Replicas));
+    check_names_dont_collide(&names, "S3 replica");
+
+    names
+        .into_iter()
+        .map(|name| {
+            if !is_valid_name(&name) {
+                panic!(
+                    "`{}` is not a valid S3 replica name! Use only lowercase letters, digits, `-` and `_`.",
+                    name
+                );
+            }
+
+            let reason = format!("It's required for the `{}` S3 replica.", name);
+            S3ReplicaEnvironment {
+                s3: S3Environment {
+                    region: get_required_env_var(S3ReplicaRegion(name.clone()), &reason),
+                    url: get_required_env_var(S3ReplicaUrl(name.clone()), &reason),
+                    bucket_name: get_required_env_var(S3ReplicaBucketName(name.clone()), &reason),
+                    access_key: get_required_env_var(S3ReplicaAccessKey(name.clone()), &reason),
+                    secret_key: get_required_env_var(S3ReplicaSecretKey(name.clone()), &reason),
+                },
+                name,
+            }
+        })
+        .collect()
+}
+
+fn build_environment() -> Environment {
+    let schedule_timezone = get_env_var::<String>(ScheduleTimezone);
+    Environment {
+        metadata: Metadata {
+            database_path: get_database_path(),
+            running_environment: get_running_environment(),
+            logs_directory_path: get_logs_directory_path(),
+            program_version: get_program_version(),
+        },
+        letterboxd: LetterboxdEnvironment {
+            accounts: get_letterboxd_accounts(),
+            backup_schedule: as_task_schedule(
+                get_env_var(LetterboxdBackupFrequency),
+                &schedule_timezone,
+            ),
+            backup_catch_up: as_catch_up_policy(get_env_var(LetterboxdBackupCatchUp)),
+            backup_timeout: as_integer(get_env_var(LetterboxdBackupTimeout)),
+            backup_enable: as_boolean(get_env_var(LetterboxdBackupEnable)),
+            retention: RetentionPolicy {
+                keep_last: as_integer(get_env_var(LetterboxdRetentionKeepLast)),
+                keep_daily: as_integer(get_env_var(LetterboxdRetentionKeepDaily)),
+                keep_weekly: as_integer(get_env_var(LetterboxdRetentionKeepWeekly)),
+                keep_monthly: as_integer(get_env_var(LetterboxdRetentionKeepMonthly)),
+            },
+        },
+        s3: S3Environment {
+            region: get_env_var(S3Region),
+            url: get_env_var(S3Url),
+            bucket_name: get_env_var(S3BucketName),
+            access_key: get_env_var(S3AccessKey),
+            secret_key: get_env_var(S3SecretKey),
+        },
+        s3_replicas: get_s3_replicas(),
+        secrets: SecretsEnvironment {
+            are_encrypted: as_boolean(get_env_var(SecretsAreEncrypted)),
+            decryption_key: get_env_var(SecretsDecryptionKey),
+            decryption_key_passphrase: decryption_key_passphrase(),
+        },
+        status_server: StatusServerEnvironment {
+            enable: as_boolean(get_env_var(StatusServerEnable)),
+            port: as_integer(get_env_var(StatusServerPort)),
+        },
+        webdriver: WebDriverEnvironment {
+            url: get_env_var(WebDriverUrl),
+            port: as_integer(get_env_var(WebDriverPort)),
+        },
+        imap: ImapEnvironment {
+            enable: as_boolean(get_env_var(ImapEnable)),
+            host: get_env_var(ImapHost),
+            port: as_integer(get_env_var(ImapPort)),
+            tls: as_boolean(get_env_var(ImapTls)),
+            username: get_env_var(ImapUsername),
+            password: get_env_var(ImapPassword),
+            mailbox: get_env_var(ImapMailbox),
+        },
+        storage: StorageEnvironment {
+            backends: as_list(get_env_var(StorageBackend)),
+            local_path: get_env_var(LocalStoragePath),
+            skip_unchanged: as_boolean(get_env_var(SkipUnchangedBackups)),
+            retention_dry_run: as_boolean(get_env_var(RetentionDryRun)),
+        },
+        backup_encryption: BackupEncryptionEnvironment {
+            enable: as_boolean(get_env_var(BackupEncryptionEnable)),
+            public_keys_paths: as_list(get_env_var(BackupEncryptionPublicKeys)),
+        },
+        verification: VerificationEnvironment {
+            enable: as_boolean(get_env_var(VerificationEnable)),
+            schedule: as_task_schedule(get_env_var(VerificationFrequency), &schedule_timezone),
+            catch_up: as_catch_up_policy(get_env_var(VerificationCatchUp)),
+            timeout: as_integer(get_env_var(VerificationTimeout)),
+        },
+        task_retry: RetryPolicy {
+            max_attempts: as_integer(get_env_var(TaskRetryMaxAttempts)),
+            initial_delay_seconds: as_integer(get_env_var(TaskRetryInitialDelay)),
+            max_delay_seconds: as_integer(get_env_var(TaskRetryMaxDelay)),
+            jitter_percentage: as_integer(get_env_var(TaskRetryJitter)),
+        },
+        scheduler: SchedulerEnvironment {
+            max_concurrent_tasks: as_integer(get_env_var(SchedulerMaxConcurrentTasks)),
+        },
+    }
+}
+
+pub fn environment() -> Arc<Environment> {
+    if let Some(environment) = ENVIRONMENT
+        .read()
+        .expect("Failed to access the environment!")
+        .as_ref()
+    {
+        return environment.clone();
+    }
+
+    let mut environment = ENVIRONMENT
+        .write()
+        .expect("Failed to access the environment!");
+    environment
+        .get_or_insert_with(|| Arc::new(build_environment()))
+        .clone()
+}
+
+// Reads the `.env` and config files again, replacing the environment only if they're valid. The
+// passphrase of the decryption key isn't prompted for, as the TUI is already running by then.
+pub async fn reload_environment() -> Result<()> {
+    disable_prompting();
+    let sources = match load_valid_value_sources(false).await {
+        Ok(sources) => sources,
+        Err(problems) => bail!("{}", describe_problems(&problems)),
+    };
+    set_value_sources(sources);
+
+    let environment = build_environment();
+    *ENVIRONMENT
+        .write()
+        .expect("Failed to access the environment!") = Some(Arc::new(environment));
+    Ok(())
+}
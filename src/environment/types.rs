@@ -1,4 +1,5 @@
 pub use crate::environment::constants::RunningEnvironment;
+use crate::schedule::tasks::utils::schedule::{CatchUpPolicy, TaskSchedule};
 use secrecy::SecretString;
 
 #[derive(Debug)]
@@ -9,12 +10,49 @@ pub struct Metadata {
     pub program_version: String,
 }
 
+// Amounts of backups to keep. A backup is pruned only if it isn't kept by any of those rules. If all
+// of them are set to 0, nothing is ever pruned.
+#[derive(Clone, Debug, Default)]
+pub struct RetentionPolicy {
+    // Most recent backups.
+    pub keep_last: u64,
+    // Most recent backups from each of the last N days, weeks and months.
+    pub keep_daily: u64,
+    pub keep_weekly: u64,
+    pub keep_monthly: u64,
+}
+
+// How failed task runs are retried. The delay starts at `initial_delay_seconds`, doubles with every
+// failed attempt up to `max_delay_seconds`, and is randomly shifted by up to `jitter_percentage`
+// percent in either direction.
+#[derive(Clone, Debug, Default)]
+pub struct RetryPolicy {
+    // Including the first attempt, so 0 and 1 both disable retries.
+    pub max_attempts: u64,
+    pub initial_delay_seconds: u64,
+    pub max_delay_seconds: u64,
+    pub jitter_percentage: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct LetterboxdAccount {
+    // `None` for the account defined with `LETTERBOXD_USERNAME` and `LETTERBOXD_PASSWORD`, which is
+    // used only if `LETTERBOXD_ACCOUNTS` is empty.
+    pub name: Option<String>,
+    pub username: String,
+    pub password: String,
+}
+
 #[derive(Debug)]
 pub struct LetterboxdEnvironment {
-    pub password: String,
-    pub username: String,
-    pub backup_frequency: u64,
+    // Each one is backed up by a separate task, under its own storage prefix.
+    pub accounts: Vec<LetterboxdAccount>,
+    pub backup_schedule: TaskSchedule,
+    pub backup_catch_up: CatchUpPolicy,
+    // In seconds, 0 disables the timeout.
+    pub backup_timeout: u64,
     pub backup_enable: bool,
+    pub retention: RetentionPolicy,
 }
 
 #[derive(Debug)]
@@ -26,6 +64,14 @@ pub struct S3Environment {
     pub secret_key: String,
 }
 
+// Additional bucket every backup is replicated to, which can have a different endpoint and
+// credentials than the main one.
+#[derive(Debug)]
+pub struct S3ReplicaEnvironment {
+    pub name: String,
+    pub s3: S3Environment,
+}
+
 #[derive(Debug)]
 pub struct SecretsEnvironment {
     // Whether the raw values of the secrets (passed via environment variables) are encrypted or
@@ -48,6 +94,53 @@ pub struct WebDriverEnvironment {
     pub url: String,
 }
 
+#[derive(Debug)]
+pub struct ImapEnvironment {
+    pub enable: bool,
+    pub host: String,
+    pub port: u64,
+    pub tls: bool,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub mailbox: String,
+}
+
+#[derive(Debug)]
+pub struct StorageEnvironment {
+    // Every backup is uploaded to all of those, and to all S3 replicas. Each one is either `s3` or
+    // `local`.
+    pub backends: Vec<String>,
+    // Directory used by the `local` backend.
+    pub local_path: String,
+    // If enabled, exports identical to the previously stored one aren't uploaded again.
+    pub skip_unchanged: bool,
+    // If enabled, backups that would be pruned are only logged.
+    pub retention_dry_run: bool,
+}
+
+#[derive(Debug)]
+pub struct BackupEncryptionEnvironment {
+    pub enable: bool,
+    // Paths to armored public keys, which backups are encrypted to. If empty, the public key of
+    // `SECRETS_DECRYPTION_KEY` is used.
+    pub public_keys_paths: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct VerificationEnvironment {
+    pub enable: bool,
+    pub schedule: TaskSchedule,
+    pub catch_up: CatchUpPolicy,
+    // In seconds, 0 disables the timeout.
+    pub timeout: u64,
+}
+
+#[derive(Debug)]
+pub struct SchedulerEnvironment {
+    // Amount of tasks allowed to run at the same time, 0 means no limit.
+    pub max_concurrent_tasks: u64,
+}
+
 #[derive(Debug)]
 pub struct Environment {
     // All additional data that is handy to have returned by the `environment` method, but isn't
@@ -55,7 +148,14 @@ pub struct Environment {
     pub metadata: Metadata,
     pub letterboxd: LetterboxdEnvironment,
     pub s3: S3Environment,
+    pub s3_replicas: Vec<S3ReplicaEnvironment>,
     pub secrets: SecretsEnvironment,
     pub status_server: StatusServerEnvironment,
     pub webdriver: WebDriverEnvironment,
+    pub imap: ImapEnvironment,
+    pub storage: StorageEnvironment,
+    pub backup_encryption: BackupEncryptionEnvironment,
+    pub verification: VerificationEnvironment,
+    pub task_retry: RetryPolicy,
+    pub scheduler: SchedulerEnvironment,
 }
@@ -0,0 +1,308 @@
+use crate::environment::constants::EnvironmentVariable;
+use crate::environment::utils::generic::{get_default_config_file_path, validate_value};
+use anyhow::{Context, Result, anyhow, bail};
+use std::collections::HashMap;
+use std::ops::Range;
+use toml::de::{DeTable, DeValue};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigValue {
+    // In the same format as the matching environment variable, e.g. lists are comma-separated.
+    pub value: String,
+    pub key: String,
+    pub line: usize,
+}
+
+// Values of the TOML config file, keyed by names of the environment variables they stand for.
+#[derive(Debug)]
+pub struct ConfigFile {
+    pub path: String,
+    values: HashMap<String, ConfigValue>,
+}
+
+impl ConfigFile {
+    pub fn get(&self, variable: &EnvironmentVariable) -> Option<&ConfigValue> {
+        self.values.get(&variable.as_str())
+    }
+}
+
+fn get_line(contents: &str, span: Range<usize>) -> usize {
+    contents[..span.start.min(contents.len())]
+        .matches('\n')
+        .count()
+        + 1
+}
+
+// Converts the value into the format of environment variables, so that it goes through the same
+// parsing (and decryption) as them.
+fn value_to_string(value: &DeValue) -> Result<String> {
+    match value {
+        DeValue::String(string) => Ok(string.to_string()),
+        DeValue::Integer(integer) => {
+            let number = i64::from_str_radix(integer.as_str(), integer.radix())
+                .context("Integer is out of range!")?;
+            Ok(number.to_string())
+        }
+        DeValue::Boolean(boolean) => Ok(boolean.to_string()),
+        DeValue::Array(array) => {
+            let entries: Result<Vec<String>> = array
+                .iter()
+                .map(|entry| match entry.get_ref() {
+                    DeValue::Array(_) | DeValue::Table(_) => {
+                        Err(anyhow!("Lists can't contain other lists or tables!"))
+                    }
+                    entry => value_to_string(entry),
+                })
+                .collect();
+            Ok(entries?.join(","))
+        }
+        value => bail!("{} values aren't supported!", value.type_str()),
+    }
+}
+
+struct ConfigFileParser<'a> {
+    path: &'a str,
+    contents: &'a str,
+    values: HashMap<String, ConfigValue>,
+}
+
+impl ConfigFileParser<'_> {
+    fn error(
+        &self,
+        span: Range<usize>,
+        key: &str,
+        message: impl std::fmt::Display,
+    ) -> anyhow::Error {
+        anyhow!(
+            "{}:{}: `{}`: {}",
+            self.path,
+            get_line(self.contents, span),
+            key,
+            message
+        )
+    }
+
+    fn parse_table(&mut self, table: &DeTable, prefix: &str) -> Result<()> {
+        for (name, value) in table.iter() {
+            let key = format!("{}{}", prefix, name.get_ref());
+
+            if let DeValue::Table(section) = value.get_ref() {
+                if !EnvironmentVariable::is_config_section(&key) {
+                    return Err(self.error(name.span(), &key, "Unknown section!"));
+                }
+                self.parse_table(section, &format!("{}.", key))?;
+                continue;
+            }
+
+            let Some(variable) = EnvironmentVariable::from_config_key(&key) else {
+                return Err(self.error(name.span(), &key, "Unknown key!"));
+            };
+            let string_value = value_to_string(value.get_ref())
+                .map_err(|error| self.error(value.span(), &key, format!("{:#}", error)))?;
+            validate_value(variable.get_value_kind(), &string_value)
+                .map_err(|error| self.error(value.span(), &key, format!("{:#}", error)))?;
+
+            self.values.insert(
+                variable.as_str(),
+                ConfigValue {
+                    value: string_value,
+                    key,
+                    line: get_line(self.contents, value.span()),
+                },
+            );
+        }
+
+        Ok(())
+    }
+}
+
+// Names of the tables defined in `section`, joined like a list, e.g. `alice,bob`.
+fn get_table_names(contents: &str, document: &DeTable, section: &str) -> Option<ConfigValue> {
+    let mut segments = section.split('.');
+    let mut value = document.get(segments.next()?)?;
+    for segment in segments {
+        value = value.get_ref().get(segment)?;
+    }
+    let DeValue::Table(table) = value.get_ref() else {
+        return None;
+    };
+
+    let names: Vec<String> = table
+        .iter()
+        .map(|(name, _)| name.get_ref().to_string())
+        .collect();
+    Some(ConfigValue {
+        value: names.join(","),
+        key: section.to_string(),
+        line: get_line(contents, value.span()),
+    })
+}
+
+// `path` is used only in error messages, which point to the file, line and key.
+pub fn parse_config_file(path: &str, contents: &str) -> Result<ConfigFile> {
+    let document = DeTable::parse(contents).map_err(|error| {
+        let line = get_line(contents, error.span().unwrap_or_default());
+        anyhow!("{}:{}: {}", path, line, error.message())
+    })?;
+
+    let mut parser = ConfigFileParser {
+        path,
+        contents,
+        values: HashMap::new(),
+    };
+    parser.parse_table(document.get_ref(), "")?;
+
+    // Accounts are defined as `[letterboxd.accounts.<name>]` tables, and S3 replicas as
+    // `[s3_replicas.<name>]` ones.
+    for (section, variable) in [
+        (
+            "letterboxd.accounts",
+            EnvironmentVariable::LetterboxdAccounts,
+        ),
+        ("s3_replicas", EnvironmentVariable::S3Replicas),
+    ] {
+        if let Some(names) = get_table_names(contents, document.get_ref(), section) {
+            parser.values.insert(variable.as_str(), names);
+        }
+    }
+
+    Ok(ConfigFile {
+        path: path.to_string(),
+        values: parser.values,
+    })
+}
+
+// `CONFIG_FILE` might be defined in the `.env` file, which is why it's passed.
+pub fn load_config_file(env_file: &HashMap<String, String>) -> Result<Option<ConfigFile>> {
+    let key = EnvironmentVariable::ConfigFile.as_str();
+    let path = match std::env::var(&key)
+        .ok()
+        .or_else(|| env_file.get(&key).cloned())
+    {
+        Some(path) if !path.is_empty() => path,
+        _ => match get_default_config_file_path() {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read the `{}` config file", path))?;
+    parse_config_file(&path, &contents).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    mod parse_config_file {
+        use super::super::*;
+
+        #[test]
+        fn maps_keys_to_environment_variables() {
+            let contents = "
+[letterboxd]
+username = \"someone\"
+backup_schedule = \"30 3 * * *\"
+
+[letterboxd.retention]
+keep_daily = 7
+
+[storage]
+backends = [\"s3\", \"local\"]
+";
+            let config_file = parse_config_file("asmda.toml", contents).unwrap();
+
+            let get_value = |variable| config_file.get(&variable).unwrap().value.clone();
+            assert_eq!(
+                get_value(EnvironmentVariable::LetterboxdUsername),
+                "someone"
+            );
+            assert_eq!(
+                get_value(EnvironmentVariable::LetterboxdBackupFrequency),
+                "30 3 * * *"
+            );
+            assert_eq!(
+                get_value(EnvironmentVariable::LetterboxdRetentionKeepDaily),
+                "7"
+            );
+            assert_eq!(get_value(EnvironmentVariable::StorageBackend), "s3,local");
+            assert_eq!(
+                config_file
+                    .get(&EnvironmentVariable::LetterboxdRetentionKeepDaily)
+                    .unwrap()
+                    .line,
+                7
+            );
+        }
+
+        #[test]
+        fn reports_file_line_and_key_of_invalid_values() {
+            let contents = "[task_retry]\nmax_attempts = 3\njitter_percentage = \"a lot\"\n";
+            let error = parse_config_file("asmda.toml", contents).unwrap_err();
+
+            assert!(
+                format!("{:#}", error).starts_with("asmda.toml:3: `task_retry.jitter_percentage`:")
+            );
+        }
+
+        #[test]
+        fn collects_named_accounts() {
+            let contents = "
+[letterboxd.accounts.alice]
+username = \"alice_films\"
+password = \"secret\"
+
+[letterboxd.accounts.bob]
+username = \"bob_films\"
+password = \"secret\"
+";
+            let config_file = parse_config_file("asmda.toml", contents).unwrap();
+
+            let get_value = |variable| config_file.get(&variable).unwrap().value.clone();
+            assert_eq!(
+                get_value(EnvironmentVariable::LetterboxdAccounts),
+                "alice,bob"
+            );
+            assert_eq!(
+                get_value(EnvironmentVariable::LetterboxdAccountUsername(
+                    "bob".to_string()
+                )),
+                "bob_films"
+            );
+        }
+
+        #[test]
+        fn collects_named_s3_replicas() {
+            let contents = "
+[s3_replicas.offsite]
+url = \"https://s3.example.com\"
+secret_key = \"secret\"
+";
+            let config_file = parse_config_file("asmda.toml", contents).unwrap();
+
+            let get_value = |variable| config_file.get(&variable).unwrap().value.clone();
+            assert_eq!(get_value(EnvironmentVariable::S3Replicas), "offsite");
+            assert_eq!(
+                get_value(EnvironmentVariable::S3ReplicaUrl("offsite".to_string())),
+                "https://s3.example.com"
+            );
+        }
+
+        #[test]
+        fn parses_the_example_file() {
+            let contents = include_str!("../../../asmda.example.toml");
+
+            assert!(parse_config_file("asmda.example.toml", contents).is_ok());
+        }
+
+        #[test]
+        fn rejects_unknown_keys() {
+            let contents = "[letterboxd]\nusername = \"someone\"\nnickname = \"someone\"\n";
+            let error = parse_config_file("asmda.toml", contents).unwrap_err();
+
+            assert_eq!(
+                format!("{:#}", error),
+                "asmda.toml:3: `letterboxd.nickname`: Unknown key!"
+            );
+        }
+    }
+}
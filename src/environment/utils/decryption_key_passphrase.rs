@@ -1,35 +1,69 @@
 use crate::environment::constants::EnvironmentVariable;
-use crate::environment::utils::environment::get_env_var_with_potential_fallback;
-use crate::environment::utils::generic::as_boolean;
+use crate::environment::utils::environment::{ValueSources, find_value, value_sources};
 use once_cell::sync::OnceCell;
 use rpassword::read_password;
 use secrecy::SecretString;
 use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-static DECRYPTION_KEY_PASSPHRASE: OnceCell<Option<SecretString>> = OnceCell::new();
-
-pub fn decryption_key_passphrase() -> &'static Option<SecretString> {
-    DECRYPTION_KEY_PASSPHRASE.get_or_init(|| {
-        let option_key_passphrase = get_env_var_with_potential_fallback(
-            EnvironmentVariable::SecretsDecryptionKeyPassphrase,
-        );
-        let key_passphrase = match option_key_passphrase {
-            Some(v) => v,
-            None => {
-                let using_encryption_str =
-                    get_env_var_with_potential_fallback(EnvironmentVariable::SecretsAreEncrypted);
-                let using_encryption = as_boolean(using_encryption_str);
-                if !using_encryption {
-                    return None;
-                }
-
-                println!("You're using the `SECRETS_ARE_ENCRYPTED` option, but decryption key's passphrase has not yet been defined. Please input it below: ");
-                print!("> ");
-                io::stdout().flush().unwrap();
-
-                read_password().expect("Failed to read the password!")
-            }
-        };
-        Some(SecretString::new(key_passphrase.into()))
-    })
+static PROMPTED_DECRYPTION_KEY_PASSPHRASE: OnceCell<SecretString> = OnceCell::new();
+// Set once the configuration is reloaded, as the TUI is running by then.
+static IS_PROMPTING_DISABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn disable_prompting() {
+    IS_PROMPTING_DISABLED.store(true, Ordering::SeqCst);
+}
+
+fn prompt_for_passphrase() -> SecretString {
+    println!(
+        "You're using the `SECRETS_ARE_ENCRYPTED` or `BACKUP_ENCRYPTION_ENABLE` option, but decryption key's passphrase has not yet been defined. Please input it below: "
+    );
+    print!("> ");
+    io::stdout().flush().unwrap();
+
+    let key_passphrase = read_password().expect("Failed to read the password!");
+    SecretString::new(key_passphrase.into())
+}
+
+// The passphrase is prompted for at most once, if it's needed but not configured. Prompting isn't
+// possible once the TUI is running, in which case only an already prompted passphrase is returned.
+pub fn get_decryption_key_passphrase(
+    sources: &ValueSources,
+    can_prompt: bool,
+) -> Option<SecretString> {
+    // Unreadable secret files are reported while validating the configuration.
+    let find_value = |variable| {
+        find_value(&variable, sources)
+            .ok()
+            .flatten()
+            .map(|found| found.value)
+    };
+
+    if let Some(key_passphrase) = find_value(EnvironmentVariable::SecretsDecryptionKeyPassphrase) {
+        return Some(SecretString::new(
+            key_passphrase.replace("\\n", "\n").into(),
+        ));
+    }
+
+    let using_encryption =
+        find_value(EnvironmentVariable::SecretsAreEncrypted).is_some_and(|value| value == "true");
+    // The key is also used for encrypting and restoring backups.
+    let using_backup_encryption = find_value(EnvironmentVariable::BackupEncryptionEnable)
+        .is_some_and(|value| value == "true");
+    if !using_encryption && !using_backup_encryption {
+        return None;
+    }
+
+    if !can_prompt || IS_PROMPTING_DISABLED.load(Ordering::SeqCst) {
+        return PROMPTED_DECRYPTION_KEY_PASSPHRASE.get().cloned();
+    }
+    Some(
+        PROMPTED_DECRYPTION_KEY_PASSPHRASE
+            .get_or_init(prompt_for_passphrase)
+            .clone(),
+    )
+}
+
+pub fn decryption_key_passphrase() -> Option<SecretString> {
+    get_decryption_key_passphrase(&value_sources(), true)
 }
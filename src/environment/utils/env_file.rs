@@ -0,0 +1,13 @@
+use std::collections::HashMap;
+
+// Values of the `.env` file. Unlike with `dotenv()`, they aren't set as variables of the process,
+// so that the file can be read again once it changes. Variables of the process still take
+// precedence over them.
+// The suggested replacement of `dotenv_iter` sets the variables, which is exactly what's avoided here.
+#[allow(deprecated)]
+pub fn load_env_file() -> HashMap<String, String> {
+    let Ok(entries) = dotenv::dotenv_iter() else {
+        return HashMap::new();
+    };
+    entries.filter_map(Result::ok).collect()
+}
@@ -1,9 +1,15 @@
 use crate::environment::constants::{EnvironmentVariable, RunningEnvironment};
+use crate::environment::utils::config_file::{ConfigFile, load_config_file};
 use crate::environment::utils::decryption_key_passphrase::decryption_key_passphrase;
+use crate::environment::utils::env_file::load_env_file;
 use crate::environment::utils::generic::{as_boolean, get_running_environment};
 use crate::utils::encryption::EncryptionManager;
 use crate::utils::multithreading;
+use anyhow::{Context, Result};
 use secrecy::ExposeSecret;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
 
 fn default_variable_value_parser(value: String) -> String {
     value.clone().replace("\\n", "\n")
@@ -31,20 +37,163 @@ impl EnvironmentVariableGetterResultParser for Option<String> {
     }
 }
 
-pub fn get_env_var_with_potential_fallback<T: EnvironmentVariableGetterResultParser>(
-    variable: EnvironmentVariable,
-) -> T {
-    let running_environment = get_running_environment();
+// Value of an environment variable together with a description of where it was read from, so that
+// problems with it can point to the place which needs fixing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoundValue {
+    pub value: String,
+    pub origin: String,
+}
+
+// Everything the values are read from, besides variables of the process. Both files are optional.
+#[derive(Debug, Default)]
+pub struct ValueSources {
+    pub env_file: HashMap<String, String>,
+    pub config_file: Option<ConfigFile>,
+}
+
+// Swapped once the files are read again, see `environment::reload_environment`.
+static VALUE_SOURCES: RwLock<Option<Arc<ValueSources>>> = RwLock::new(None);
+
+pub fn value_sources() -> Arc<ValueSources> {
+    if let Some(sources) = VALUE_SOURCES
+        .read()
+        .expect("Failed to access value sources!")
+        .as_ref()
+    {
+        return sources.clone();
+    }
+
+    let mut sources = VALUE_SOURCES
+        .write()
+        .expect("Failed to access value sources!");
+    sources
+        .get_or_insert_with(|| {
+            let env_file = load_env_file();
+            let config_file = load_config_file(&env_file)
+                .unwrap_or_else(|error| panic!("Invalid config file! {:#}", error));
+            Arc::new(ValueSources {
+                env_file,
+                config_file,
+            })
+        })
+        .clone()
+}
+
+pub fn set_value_sources(sources: ValueSources) {
+    *VALUE_SOURCES
+        .write()
+        .expect("Failed to access value sources!") = Some(Arc::new(sources));
+}
+
+// Editors and `echo` usually add a trailing newline, which isn't a part of the secret.
+fn read_secret_file(path: &Path) -> Result<String> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read the secret from {}!", path.display()))?;
+    Ok(contents.trim_end_matches(['\n', '\r']).to_string())
+}
+
+// Secrets can be passed as files, e.g. Docker secrets with `S3_SECRET_KEY_FILE=/run/secrets/s3`, or
+// systemd credentials (`LoadCredential=S3_SECRET_KEY:...`) found in `credentials_directory`.
+fn find_secret_file_value(
+    variable: &EnvironmentVariable,
+    sources: &ValueSources,
+    credentials_directory: Option<&Path>,
+) -> Result<Option<FoundValue>> {
     let key = variable.as_str();
-    let fallback = variable.get_development_fallback_value();
+    let file_key = format!("{}_FILE", key);
 
-    let value: Option<String> =
-        if running_environment == RunningEnvironment::Development && fallback.is_some() {
-            Some(std::env::var(key.clone()).unwrap_or_else(|_| fallback.unwrap().to_string()))
-        } else {
-            std::env::var(&key).ok()
-        };
+    let file_path = std::env::var(&file_key)
+        .ok()
+        .or_else(|| sources.env_file.get(&file_key).cloned());
+    if let Some(file_path) = file_path.filter(|path| !path.is_empty()) {
+        let value =
+            read_secret_file(Path::new(&file_path)).with_context(|| format!("`{}`", file_key))?;
+        return Ok(Some(FoundValue {
+            value,
+            origin: format!("file {} (`{}`)", file_path, file_key),
+        }));
+    }
 
+    let Some(credential_path) = credentials_directory.map(|directory| directory.join(&key)) else {
+        return Ok(None);
+    };
+    if !credential_path.exists() {
+        return Ok(None);
+    }
+    let value = read_secret_file(&credential_path)?;
+    Ok(Some(FoundValue {
+        value,
+        origin: format!("systemd credential {}", credential_path.display()),
+    }))
+}
+
+fn find_value_in(
+    variable: &EnvironmentVariable,
+    sources: &ValueSources,
+    credentials_directory: Option<&Path>,
+) -> Result<Option<FoundValue>> {
+    let key = variable.as_str();
+
+    if let Ok(value) = std::env::var(&key) {
+        return Ok(Some(FoundValue {
+            value,
+            origin: format!("environment variable `{}`", key),
+        }));
+    }
+    if variable.is_secret()
+        && let Some(found) = find_secret_file_value(variable, sources, credentials_directory)?
+    {
+        return Ok(Some(found));
+    }
+    if let Some(value) = sources.env_file.get(&key) {
+        return Ok(Some(FoundValue {
+            value: value.clone(),
+            origin: format!("`{}` in the .env file", key),
+        }));
+    }
+    if let Some(config_file) = &sources.config_file
+        && let Some(config_value) = config_file.get(variable)
+    {
+        return Ok(Some(FoundValue {
+            value: config_value.value.clone(),
+            origin: format!(
+                "`{}` in {}:{}",
+                config_value.key, config_file.path, config_value.line
+            ),
+        }));
+    }
+    let fallback = match get_running_environment() {
+        RunningEnvironment::Development => variable.get_development_fallback_value(),
+        RunningEnvironment::Production => None,
+    };
+    Ok(fallback.map(|value| FoundValue {
+        value,
+        origin: format!("development fallback of `{}`", key),
+    }))
+}
+
+// Variables of the process take precedence over secret files, which take precedence over the
+// `.env` file, which takes precedence over the config file, which takes precedence over
+// development fallbacks. Fails only if a secret file exists, but can't be read.
+pub fn find_value(
+    variable: &EnvironmentVariable,
+    sources: &ValueSources,
+) -> Result<Option<FoundValue>> {
+    let credentials_directory = std::env::var("CREDENTIALS_DIRECTORY").ok();
+    find_value_in(
+        variable,
+        sources,
+        credentials_directory.as_deref().map(Path::new),
+    )
+}
+
+pub fn get_env_var_with_potential_fallback<T: EnvironmentVariableGetterResultParser>(
+    variable: EnvironmentVariable,
+) -> T {
+    let value = find_value(&variable, &value_sources())
+        .unwrap_or_else(|error| panic!("{:#}", error))
+        .map(|found| found.value);
     T::from_result(value, variable)
 }
 
@@ -63,7 +212,8 @@ async fn get_env_var_async<T: EnvironmentVariableGetterResultParser>(
         let option_key: Option<String> =
             get_env_var_with_potential_fallback(EnvironmentVariable::SecretsDecryptionKey);
         let key = option_key.expect("Encryption key is not defined, even though `SECRETS_ARE_ENCRYPTED` is set to true. Please configure it and rerun the program");
-        let key_passphrase = decryption_key_passphrase().clone().unwrap();
+        let key_passphrase = decryption_key_passphrase()
+            .expect("Decryption key's passphrase is not defined, even though `SECRETS_ARE_ENCRYPTED` is set to true.");
 
         let encryption_manager =
             EncryptionManager::new(key, key_passphrase.expose_secret().to_string()).await;
@@ -79,3 +229,177 @@ pub fn get_env_var<T: EnvironmentVariableGetterResultParser + Send + 'static>(
 ) -> T {
     multithreading::block_on(get_env_var_async(variable))
 }
+
+#[cfg(test)]
+mod tests {
+    mod find_value_in {
+        use super::super::*;
+        use crate::utils::uuid;
+        use std::path::PathBuf;
+
+        fn create_directory() -> PathBuf {
+            let directory = std::env::temp_dir().join(format!("asmda_{}", uuid::get_random()));
+            std::fs::create_dir_all(&directory).unwrap();
+            directory
+        }
+
+        fn get_replica() -> String {
+            "offsite".to_string()
+        }
+
+        fn get_sources(env_file: &[(&str, &str)]) -> ValueSources {
+            ValueSources {
+                env_file: env_file
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+                config_file: None,
+            }
+        }
+
+        #[test]
+        fn reads_secrets_from_files() {
+            let directory = create_directory();
+            let secret_path = directory.join("s3");
+            std::fs::write(&secret_path, "secret\n").unwrap();
+            let sources = get_sources(&[
+                (
+                    "S3_REPLICA_OFFSITE_SECRET_KEY_FILE",
+                    secret_path.to_str().unwrap(),
+                ),
+                ("S3_REPLICA_OFFSITE_SECRET_KEY", "overridden"),
+            ]);
+
+            let found = find_value_in(
+                &EnvironmentVariable::S3ReplicaSecretKey(get_replica()),
+                &sources,
+                None,
+            )
+            .unwrap()
+            .unwrap();
+
+            assert_eq!(found.value, "secret");
+            std::fs::remove_dir_all(directory).unwrap();
+        }
+
+        #[test]
+        fn reads_systemd_credentials() {
+            let directory = create_directory();
+            std::fs::write(directory.join("S3_REPLICA_OFFSITE_SECRET_KEY"), "secret").unwrap();
+            std::fs::write(directory.join("S3_REPLICA_OFFSITE_URL"), "http://localhost").unwrap();
+            let sources = get_sources(&[]);
+
+            let find = |variable| {
+                find_value_in(&variable, &sources, Some(&directory))
+                    .unwrap()
+                    .map(|found| found.value)
+            };
+
+            assert_eq!(
+                find(EnvironmentVariable::S3ReplicaSecretKey(get_replica())),
+                Some("secret".to_string())
+            );
+            // Only secrets can be read from files.
+            assert_eq!(find(EnvironmentVariable::S3ReplicaUrl(get_replica())), None);
+            std::fs::remove_dir_all(directory).unwrap();
+        }
+
+        #[test]
+        fn reports_unreadable_secret_files() {
+            let sources = get_sources(&[("S3_REPLICA_OFFSITE_SECRET_KEY_FILE", "/nonexistent/s3")]);
+
+            let error = find_value_in(
+                &EnvironmentVariable::S3ReplicaSecretKey(get_replica()),
+                &sources,
+                None,
+            )
+            .unwrap_err();
+
+            assert!(format!("{:#}", error).starts_with(
+                "`S3_REPLICA_OFFSITE_SECRET_KEY_FILE`: Failed to read the secret from /nonexistent/s3!"
+            ));
+        }
+    }
+
+    mod accounts {
+        use super::super::*;
+        use crate::utils::uuid;
+
+        // Unique, so that its variables aren't read by other tests.
+        fn get_account() -> String {
+            format!("tests-{}", uuid::get_random())
+        }
+
+        #[test]
+        fn reads_account_credentials_from_environment_variables() {
+            let account = get_account();
+            let username = EnvironmentVariable::LetterboxdAccountUsername(account.clone());
+            let password = EnvironmentVariable::LetterboxdAccountPassword(account);
+            let sources = ValueSources {
+                env_file: HashMap::from([(password.as_str(), "overridden".to_string())]),
+                config_file: None,
+            };
+            // SAFETY: No other test reads variables of this account.
+            unsafe {
+                std::env::set_var(username.as_str(), "someone");
+                std::env::set_var(password.as_str(), "secret");
+            }
+
+            let found_username = find_value_in(&username, &sources, None).unwrap();
+            let found_password = find_value_in(&password, &sources, None).unwrap();
+            unsafe {
+                std::env::remove_var(username.as_str());
+                std::env::remove_var(password.as_str());
+            }
+
+            assert_eq!(
+                found_username,
+                Some(FoundValue {
+                    value: "someone".to_string(),
+                    origin: format!("environment variable `{}`", username.as_str()),
+                })
+            );
+            assert_eq!(found_password.unwrap().value, "secret");
+        }
+
+        #[test]
+        fn prefixes_variables_with_names() {
+            let account = "my-account".to_string();
+            let replica = "off_site".to_string();
+
+            assert_eq!(
+                EnvironmentVariable::LetterboxdAccountUsername(account.clone()).as_str(),
+                "LETTERBOXD_MY_ACCOUNT_USERNAME"
+            );
+            assert_eq!(
+                EnvironmentVariable::LetterboxdAccountPassword(account).as_str(),
+                "LETTERBOXD_MY_ACCOUNT_PASSWORD"
+            );
+            assert_eq!(
+                EnvironmentVariable::S3ReplicaBucketName(replica).as_str(),
+                "S3_REPLICA_OFF_SITE_BUCKET_NAME"
+            );
+        }
+
+        #[test]
+        fn keeps_variables_of_accounts_apart() {
+            let sources = ValueSources {
+                env_file: HashMap::from([
+                    ("LETTERBOXD_ALICE_USERNAME".to_string(), "alice".to_string()),
+                    ("LETTERBOXD_BOB_USERNAME".to_string(), "bob".to_string()),
+                ]),
+                config_file: None,
+            };
+            let find = |account: &str| {
+                let variable = EnvironmentVariable::LetterboxdAccountUsername(account.to_string());
+                find_value_in(&variable, &sources, None)
+                    .unwrap()
+                    .map(|found| found.value)
+            };
+
+            assert_eq!(find("alice"), Some("alice".to_string()));
+            assert_eq!(find("bob"), Some("bob".to_string()));
+            assert_eq!(find("carol"), None);
+        }
+    }
+}
@@ -1,5 +1,8 @@
-use crate::environment::constants::RunningEnvironment;
+use crate::environment::constants::{RunningEnvironment, ValueKind, get_variable_name_part};
+use crate::schedule::tasks::utils::schedule::{CatchUpPolicy, TaskSchedule, parse_timezone};
+use anyhow::{Context, Result};
 use dirs;
+use std::path::Path;
 
 pub fn as_boolean(value: String) -> bool {
     value
@@ -13,6 +16,75 @@ pub fn as_integer(value: String) -> u64 {
         .unwrap_or_else(|_| panic!("Failed to cast {} into an u64!", &value))
 }
 
+// Either an amount of seconds or a cron expression, see `TaskSchedule::parse`.
+pub fn as_task_schedule(value: String, timezone: &str) -> TaskSchedule {
+    TaskSchedule::parse(&value, timezone)
+        .unwrap_or_else(|error| panic!("Failed to cast {} into a schedule! {:#}", &value, error))
+}
+
+// Either `once`, `skip` or `all`, see `CatchUpPolicy`.
+pub fn as_catch_up_policy(value: String) -> CatchUpPolicy {
+    CatchUpPolicy::parse(&value).unwrap_or_else(|error| {
+        panic!(
+            "Failed to cast {} into a catch-up policy! {:#}",
+            &value, error
+        )
+    })
+}
+
+// Splits comma-separated values, e.g. `"s3, local"`.
+pub fn as_list(value: String) -> Vec<String> {
+    value
+        .split(',')
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+// Names of accounts and S3 replicas are used in storage prefixes and names of environment variables,
+// so they're restricted to lowercase letters, digits, `-` and `_`.
+pub fn is_valid_name(name: &str) -> bool {
+    name.chars().all(|character| {
+        character.is_ascii_lowercase()
+            || character.is_ascii_digit()
+            || character == '-'
+            || character == '_'
+    })
+}
+
+// Pairs of names sharing environment variables, e.g. `a-b` and `a_b`, which both use `A_B`.
+pub fn find_colliding_names(names: &[String]) -> Vec<(&str, &str)> {
+    let mut collisions = vec![];
+    for (index, name) in names.iter().enumerate() {
+        let other_name = names[..index].iter().find(|other_name| {
+            *other_name != name
+                && get_variable_name_part(other_name) == get_variable_name_part(name)
+        });
+        if let Some(other_name) = other_name {
+            collisions.push((other_name.as_str(), name.as_str()));
+        }
+    }
+    collisions
+}
+
+// Checks whether `value` can be cast by the function matching `kind`, without panicking.
+pub fn validate_value(kind: ValueKind, value: &str) -> Result<()> {
+    match kind {
+        ValueKind::Text | ValueKind::List => Ok(()),
+        ValueKind::Boolean => value
+            .parse::<bool>()
+            .map(|_| ())
+            .with_context(|| format!("`{}` is not a boolean!", value)),
+        ValueKind::Integer => value
+            .parse::<u64>()
+            .map(|_| ())
+            .with_context(|| format!("`{}` is not a non-negative integer!", value)),
+        ValueKind::Schedule => TaskSchedule::parse(value, "local").map(|_| ()),
+        ValueKind::Timezone => parse_timezone(value).map(|_| ()),
+        ValueKind::CatchUpPolicy => CatchUpPolicy::parse(value).map(|_| ()),
+    }
+}
+
 pub fn get_running_environment() -> RunningEnvironment {
     if cfg!(debug_assertions) {
         return RunningEnvironment::Development;
@@ -54,3 +126,26 @@ pub fn get_logs_directory_path() -> String {
 pub fn get_program_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
+
+// Used if `CONFIG_FILE` isn't set. `None` if there's no file there, as the config file is optional.
+pub fn get_default_config_file_path() -> Option<String> {
+    let running_environment = get_running_environment();
+
+    let path = if running_environment == RunningEnvironment::Production {
+        dirs::config_dir()
+            .expect("Failed to get the config dir!")
+            .join("asmda")
+            .join("config.toml")
+    } else {
+        Path::new("asmda.toml").to_path_buf()
+    };
+
+    if !path.exists() {
+        return None;
+    }
+    Some(
+        path.to_str()
+            .expect("Failed to convert to str!")
+            .to_string(),
+    )
+}
@@ -1,3 +1,6 @@
+pub mod config_file;
 pub mod decryption_key_passphrase;
+pub mod env_file;
 pub mod environment;
 pub mod generic;
+pub mod validation;
@@ -0,0 +1,502 @@
+use crate::environment::constants::EnvironmentVariable;
+use crate::environment::constants::EnvironmentVariable::*;
+use crate::environment::utils::config_file::load_config_file;
+use crate::environment::utils::decryption_key_passphrase::get_decryption_key_passphrase;
+use crate::environment::utils::env_file::load_env_file;
+use crate::environment::utils::environment::{FoundValue, ValueSources, find_value};
+use crate::environment::utils::generic::{
+    as_list, find_colliding_names, is_valid_name, validate_value,
+};
+use crate::utils::encryption::EncryptionManager;
+use anyhow::Result;
+use secrecy::ExposeSecret;
+use strum::IntoEnumIterator;
+
+// Lets tests check values without setting environment variables.
+type ValueGetter<'a> = dyn Fn(&EnvironmentVariable) -> Result<Option<FoundValue>> + Sync + 'a;
+
+// Values which can't be read are reported separately, by `collect_value_problems`.
+fn get_readable_value(
+    get_value: &ValueGetter<'_>,
+    variable: &EnvironmentVariable,
+) -> Option<FoundValue> {
+    get_value(variable).ok().flatten()
+}
+
+// Names listed in `variable`, e.g. Letterboxd accounts.
+fn get_names(get_value: &ValueGetter<'_>, variable: &EnvironmentVariable) -> Vec<String> {
+    as_list(get_readable_value(get_value, variable).map_or_else(String::new, |found| found.value))
+}
+
+// Names of the configured Letterboxd accounts and S3 replicas, which have variables of their own.
+struct Names {
+    accounts: Vec<String>,
+    s3_replicas: Vec<String>,
+}
+
+impl Names {
+    fn get(get_value: &ValueGetter<'_>) -> Self {
+        Self {
+            accounts: get_names(get_value, &LetterboxdAccounts),
+            s3_replicas: get_names(get_value, &S3Replicas),
+        }
+    }
+}
+
+fn get_account_variables(account: &str) -> Vec<EnvironmentVariable> {
+    vec![
+        LetterboxdAccountUsername(account.to_string()),
+        LetterboxdAccountPassword(account.to_string()),
+    ]
+}
+
+fn get_s3_replica_variables(replica: &str) -> Vec<EnvironmentVariable> {
+    vec![
+        S3ReplicaRegion(replica.to_string()),
+        S3ReplicaUrl(replica.to_string()),
+        S3ReplicaBucketName(replica.to_string()),
+        S3ReplicaAccessKey(replica.to_string()),
+        S3ReplicaSecretKey(replica.to_string()),
+    ]
+}
+
+const SUPPORTED_STORAGE_BACKENDS: [&str; 2] = ["s3", "local"];
+
+fn describe_missing(variable: &EnvironmentVariable, reason: &str) -> String {
+    let location = match variable.get_config_key() {
+        Some(key) => format!(
+            "Set the environment variable, or `{}` in the config file.",
+            key
+        ),
+        None => String::from("Set the environment variable."),
+    };
+    format!("`{}` is not set! {}{}", variable.as_str(), reason, location)
+}
+
+fn is_enabled(value: Option<FoundValue>) -> bool {
+    value.is_some_and(|found| found.value == "true")
+}
+
+// Variables which are required only with some other options, along with the explanation why.
+fn get_conditionally_required_variables(
+    get_value: &ValueGetter<'_>,
+    names: &Names,
+) -> Vec<(EnvironmentVariable, String)> {
+    let mut variables = vec![];
+
+    if names.accounts.is_empty() {
+        let reason = "It's required unless `LETTERBOXD_ACCOUNTS` is defined. ";
+        variables.push((LetterboxdUsername, reason.to_string()));
+        variables.push((LetterboxdPassword, reason.to_string()));
+    }
+    for account in &names.accounts {
+        let reason = format!("It's required for the `{}` Letterboxd account. ", account);
+        for variable in get_account_variables(account) {
+            variables.push((variable, reason.clone()));
+        }
+    }
+
+    for replica in &names.s3_replicas {
+        let reason = format!("It's required for the `{}` S3 replica. ", replica);
+        for variable in get_s3_replica_variables(replica) {
+            variables.push((variable, reason.clone()));
+        }
+    }
+
+    if is_enabled(get_readable_value(get_value, &ImapEnable)) {
+        let reason = "It's required when `IMAP_ENABLE` is set to `true`. ";
+        variables.push((ImapUsername, reason.to_string()));
+        variables.push((ImapPassword, reason.to_string()));
+    }
+
+    if is_enabled(get_readable_value(get_value, &SecretsAreEncrypted)) {
+        let reason = "It's required when `SECRETS_ARE_ENCRYPTED` is set to `true`. ";
+        variables.push((SecretsDecryptionKey, reason.to_string()));
+    }
+
+    variables
+}
+
+// Every variable which might be read while building the environment, including the ones defined
+// for each of the accounts and S3 replicas.
+fn get_all_variables(names: &Names) -> Vec<EnvironmentVariable> {
+    let mut variables: Vec<EnvironmentVariable> = EnvironmentVariable::iter()
+        .filter(|variable| {
+            !matches!(
+                variable,
+                LetterboxdAccountUsername(_)
+                    | LetterboxdAccountPassword(_)
+                    | S3ReplicaRegion(_)
+                    | S3ReplicaUrl(_)
+                    | S3ReplicaBucketName(_)
+                    | S3ReplicaAccessKey(_)
+                    | S3ReplicaSecretKey(_)
+            )
+        })
+        .collect();
+    for account in &names.accounts {
+        variables.extend(get_account_variables(account));
+    }
+    for replica in &names.s3_replicas {
+        variables.extend(get_s3_replica_variables(replica));
+    }
+    variables
+}
+
+// Problems which can be found without decrypting anything, i.e. missing and malformed values.
+fn collect_value_problems(get_value: &ValueGetter<'_>) -> Vec<String> {
+    let mut problems = vec![];
+
+    let names = Names::get(get_value);
+    for (kind, listed_names) in [
+        ("Letterboxd account", &names.accounts),
+        ("S3 replica", &names.s3_replicas),
+    ] {
+        for name in listed_names {
+            if !is_valid_name(name) {
+                problems.push(format!(
+                    "`{}` is not a valid {} name! Use only lowercase letters, digits, `-` and `_`.",
+                    name, kind
+                ));
+            }
+        }
+        for (name, other_name) in find_colliding_names(listed_names) {
+            problems.push(format!(
+                "`{}` and `{}` are {} names sharing environment variables! Rename one of them.",
+                name, other_name, kind
+            ));
+        }
+    }
+
+    for variable in get_all_variables(&names) {
+        match get_value(&variable) {
+            Ok(Some(found)) => {
+                if let Err(error) = validate_value(variable.get_value_kind(), &found.value) {
+                    problems.push(format!("{}: {:#}", found.origin, error));
+                }
+            }
+            Ok(None) if variable.is_required() => problems.push(describe_missing(&variable, "")),
+            Ok(None) => {}
+            Err(error) => problems.push(format!("{:#}", error)),
+        }
+    }
+
+    for (variable, reason) in get_conditionally_required_variables(get_value, &names) {
+        if matches!(get_value(&variable), Ok(None)) {
+            problems.push(describe_missing(&variable, &reason));
+        }
+    }
+
+    if let Some(found) = get_readable_value(get_value, &StorageBackend) {
+        for backend in as_list(found.value) {
+            if !SUPPORTED_STORAGE_BACKENDS.contains(&backend.as_str()) {
+                problems.push(format!(
+                    "{}: Unknown storage backend `{}`! Supported ones are `s3` and `local`, S3 replicas are defined with `S3_REPLICAS`.",
+                    found.origin, backend
+                ));
+            }
+        }
+    }
+
+    if is_enabled(get_readable_value(get_value, &BackupEncryptionEnable))
+        && let Some(found) = get_readable_value(get_value, &BackupEncryptionPublicKeys)
+    {
+        for path in as_list(found.value) {
+            if let Err(error) = std::fs::read(&path) {
+                problems.push(format!(
+                    "{}: Failed to read the public key from {}! ({})",
+                    found.origin, path, error
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+// Decrypting requires the key's passphrase, which might have to be prompted for, so it's done only
+// once the rest of the configuration is valid.
+async fn collect_decryption_problems(sources: &ValueSources, can_prompt: bool) -> Vec<String> {
+    let get_value = |variable: &EnvironmentVariable| find_value(variable, sources);
+    let get_value: &ValueGetter<'_> = &get_value;
+
+    let using_encryption = is_enabled(get_readable_value(get_value, &SecretsAreEncrypted));
+    let using_backup_encryption =
+        is_enabled(get_readable_value(get_value, &BackupEncryptionEnable));
+    if !using_encryption && !using_backup_encryption {
+        return vec![];
+    }
+    // Building the environment would prompt for it otherwise, which isn't possible once the TUI is
+    // running, e.g. if encryption gets enabled while reloading the configuration.
+    let Some(key_passphrase) = get_decryption_key_passphrase(sources, can_prompt) else {
+        return vec![
+            "Decryption key's passphrase is unknown, and can't be prompted for while the program is running! Set `SECRETS_DECRYPTION_KEY_PASSPHRASE`, or restart the program to input it.".to_string(),
+        ];
+    };
+
+    if !using_encryption {
+        return vec![];
+    }
+    let Some(key) = get_readable_value(get_value, &SecretsDecryptionKey) else {
+        return vec![];
+    };
+
+    let encryption_manager = match EncryptionManager::try_new(
+        key.value.replace("\\n", "\n"),
+        key_passphrase.expose_secret().to_string(),
+    )
+    .await
+    {
+        Ok(encryption_manager) => encryption_manager,
+        Err(error) => return vec![format!("{}: {:#}", key.origin, error)],
+    };
+
+    let names = Names::get(get_value);
+    let mut problems = vec![];
+    for variable in get_all_variables(&names) {
+        if !variable.can_be_encrypted() {
+            continue;
+        }
+        let Some(found) = get_readable_value(get_value, &variable) else {
+            continue;
+        };
+        // Fallbacks are never encrypted, see `get_env_var`.
+        if Some(&found.value) == variable.get_development_fallback_value().as_ref() {
+            continue;
+        }
+
+        let encrypted = found.value.replace("\\n", "\n");
+        if let Err(error) = encryption_manager.try_decrypt(encrypted).await {
+            problems.push(format!("{}: {:#}", found.origin, error));
+        }
+    }
+    problems
+}
+
+// Reads the `.env` and config files, and collects all problems with the values at once, so that
+// they can be reported instead of panicking on the first one while building the environment.
+// `can_prompt` tells whether the decryption key's passphrase can be prompted for.
+pub async fn load_valid_value_sources(can_prompt: bool) -> Result<ValueSources, Vec<String>> {
+    let mut problems = vec![];
+    let env_file = load_env_file();
+    let config_file = load_config_file(&env_file).unwrap_or_else(|error| {
+        problems.push(format!("Invalid config file! {:#}", error));
+        None
+    });
+    let sources = ValueSources {
+        env_file,
+        config_file,
+    };
+
+    problems.extend(collect_value_problems(&|variable| {
+        find_value(variable, &sources)
+    }));
+    if problems.is_empty() {
+        problems.extend(collect_decryption_problems(&sources, can_prompt).await);
+    }
+
+    match problems.is_empty() {
+        true => Ok(sources),
+        false => Err(problems),
+    }
+}
+
+pub async fn validate_configuration() -> Vec<String> {
+    load_valid_value_sources(true)
+        .await
+        .err()
+        .unwrap_or_default()
+}
+
+pub fn describe_problems(problems: &[String]) -> String {
+    let mut description = format!(
+        "Found {} problem(s) with the configuration:",
+        problems.len()
+    );
+    for problem in problems {
+        description.push_str(&format!("\n- {}", problem));
+    }
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    mod collect_value_problems {
+        use super::super::*;
+        use std::collections::HashMap;
+
+        fn get_problems(values: &[(EnvironmentVariable, &str)]) -> Vec<String> {
+            let values: HashMap<String, String> = values
+                .iter()
+                .map(|(variable, value)| (variable.as_str(), value.to_string()))
+                .collect();
+            let get_value = |variable: &EnvironmentVariable| {
+                Ok(values.get(&variable.as_str()).map(|value| FoundValue {
+                    value: value.clone(),
+                    origin: format!("environment variable `{}`", variable.as_str()),
+                }))
+            };
+            collect_value_problems(&get_value)
+        }
+
+        fn get_valid_values() -> Vec<(EnvironmentVariable, &'static str)> {
+            vec![
+                (LetterboxdUsername, "someone"),
+                (LetterboxdPassword, "secret"),
+            ]
+        }
+
+        #[test]
+        fn accepts_a_valid_configuration() {
+            assert_eq!(get_problems(&get_valid_values()), Vec::<String>::new());
+        }
+
+        #[test]
+        fn collects_every_problem_at_once() {
+            let values = vec![
+                (LetterboxdUsername, "someone"),
+                (WebDriverPort, "abc"),
+                (ImapEnable, "yes"),
+                (StorageBackend, "s3,ftp"),
+            ];
+
+            let problems = get_problems(&values);
+
+            assert_eq!(
+                problems,
+                vec![
+                    "environment variable `WEBDRIVER_PORT`: `abc` is not a non-negative integer!: invalid digit found in string".to_string(),
+                    "environment variable `IMAP_ENABLE`: `yes` is not a boolean!: provided string was not `true` or `false`".to_string(),
+                    "`LETTERBOXD_PASSWORD` is not set! It's required unless `LETTERBOXD_ACCOUNTS` is defined. Set the environment variable, or `letterboxd.password` in the config file.".to_string(),
+                    "environment variable `STORAGE_BACKEND`: Unknown storage backend `ftp`! Supported ones are `s3` and `local`, S3 replicas are defined with `S3_REPLICAS`.".to_string(),
+                ]
+            );
+        }
+
+        #[test]
+        fn requires_credentials_of_each_account() {
+            let values = vec![
+                (LetterboxdAccounts, "alice,Bob"),
+                (LetterboxdAccountUsername("alice".to_string()), "alice"),
+            ];
+
+            let problems = get_problems(&values);
+
+            assert_eq!(
+                problems,
+                vec![
+                    "`Bob` is not a valid Letterboxd account name! Use only lowercase letters, digits, `-` and `_`.".to_string(),
+                    "`LETTERBOXD_ALICE_PASSWORD` is not set! It's required for the `alice` Letterboxd account. Set the environment variable, or `letterboxd.accounts.alice.password` in the config file.".to_string(),
+                    "`LETTERBOXD_BOB_USERNAME` is not set! It's required for the `Bob` Letterboxd account. Set the environment variable, or `letterboxd.accounts.Bob.username` in the config file.".to_string(),
+                    "`LETTERBOXD_BOB_PASSWORD` is not set! It's required for the `Bob` Letterboxd account. Set the environment variable, or `letterboxd.accounts.Bob.password` in the config file.".to_string(),
+                ]
+            );
+        }
+
+        #[test]
+        fn requires_settings_of_each_s3_replica() {
+            let mut values = get_valid_values();
+            values.push((S3Replicas, "offsite"));
+            for variable in get_s3_replica_variables("offsite") {
+                if !matches!(variable, S3ReplicaSecretKey(_)) {
+                    values.push((variable, "value"));
+                }
+            }
+
+            let problems = get_problems(&values);
+
+            assert_eq!(
+                problems,
+                vec![
+                    "`S3_REPLICA_OFFSITE_SECRET_KEY` is not set! It's required for the `offsite` S3 replica. Set the environment variable, or `s3_replicas.offsite.secret_key` in the config file.".to_string(),
+                ]
+            );
+        }
+
+        #[test]
+        fn rejects_names_sharing_variables() {
+            let mut values = get_valid_values();
+            values.push((LetterboxdAccounts, "a-b,a_b"));
+            values.push((S3Replicas, "off-site,off_site"));
+            for name in ["a-b", "a_b"] {
+                for variable in get_account_variables(name) {
+                    values.push((variable, "value"));
+                }
+            }
+            for name in ["off-site", "off_site"] {
+                for variable in get_s3_replica_variables(name) {
+                    values.push((variable, "value"));
+                }
+            }
+
+            let problems = get_problems(&values);
+
+            assert_eq!(
+                problems,
+                vec![
+                    "`a-b` and `a_b` are Letterboxd account names sharing environment variables! Rename one of them.".to_string(),
+                    "`off-site` and `off_site` are S3 replica names sharing environment variables! Rename one of them.".to_string(),
+                ]
+            );
+        }
+
+        #[test]
+        fn requires_readable_public_keys() {
+            let mut values = get_valid_values();
+            values.push((BackupEncryptionEnable, "true"));
+            values.push((BackupEncryptionPublicKeys, "/nonexistent/key.asc"));
+
+            let problems = get_problems(&values);
+
+            assert_eq!(
+                problems,
+                vec![
+                    "environment variable `BACKUP_ENCRYPTION_PUBLIC_KEYS`: Failed to read the public key from /nonexistent/key.asc! (No such file or directory (os error 2))".to_string(),
+                ]
+            );
+        }
+    }
+
+    mod collect_decryption_problems {
+        use super::super::*;
+        use std::collections::HashMap;
+
+        fn get_sources(env_file: &[(EnvironmentVariable, &str)]) -> ValueSources {
+            ValueSources {
+                env_file: env_file
+                    .iter()
+                    .map(|(variable, value)| (variable.as_str(), value.to_string()))
+                    .collect::<HashMap<_, _>>(),
+                config_file: None,
+            }
+        }
+
+        #[tokio::test]
+        async fn requires_a_restart_for_an_unknown_passphrase() {
+            for variable in [SecretsAreEncrypted, BackupEncryptionEnable] {
+                let sources = get_sources(&[(variable, "true")]);
+
+                let problems = collect_decryption_problems(&sources, false).await;
+
+                assert_eq!(
+                    problems,
+                    vec![
+                        "Decryption key's passphrase is unknown, and can't be prompted for while the program is running! Set `SECRETS_DECRYPTION_KEY_PASSPHRASE`, or restart the program to input it.".to_string(),
+                    ]
+                );
+            }
+        }
+
+        #[tokio::test]
+        async fn accepts_a_configured_passphrase() {
+            let sources = get_sources(&[
+                (BackupEncryptionEnable, "true"),
+                (SecretsDecryptionKeyPassphrase, "passphrase"),
+            ]);
+
+            let problems = collect_decryption_problems(&sources, false).await;
+
+            assert_eq!(problems, Vec::<String>::new());
+        }
+    }
+}
@@ -1,4 +1,8 @@
 use crate::logger::logger;
+use crate::schedule::tasks::{Task, get_enabled_tasks, request_run_now, set_task_paused};
+use crate::tui::table::tasks_table::selection::{
+    get_selected_row_id, select_next_row, select_previous_row,
+};
 use crate::tui::tui;
 use crate::utils::constants::LOCAL_POLLING_RATE_MS;
 use crate::utils::exit::exit;
@@ -16,6 +20,42 @@ fn suspend() {
     }
 }
 
+// Rows of the tasks table are the enabled tasks, see `options::run`.
+fn get_selected_task() -> Option<Task> {
+    let Some(id) = get_selected_row_id() else {
+        logger().log("Select a task with the arrow keys first!");
+        return None;
+    };
+    get_enabled_tasks().into_iter().nth(id - 1)
+}
+
+// Asks the Scheduler to run the task selected in the tasks table.
+fn run_selected_task_now(reschedule: bool) {
+    let Some(task) = get_selected_task() else {
+        return;
+    };
+
+    let app_name = task.get_app_name().as_str();
+    match request_run_now(&app_name, reschedule) {
+        Ok(()) => logger().log(&format!("Requested running {} task now.", app_name)),
+        Err(error) => logger().error(&format!("{:#}", error)),
+    }
+}
+
+fn toggle_selected_task_pause() {
+    let Some(task) = get_selected_task() else {
+        return;
+    };
+
+    let app_name = task.get_app_name().as_str();
+    let is_paused = !task.is_paused();
+    match set_task_paused(&app_name, is_paused) {
+        Ok(()) if is_paused => logger().log(&format!("Paused {} task.", app_name)),
+        Ok(()) => logger().log(&format!("Resumed {} task.", app_name)),
+        Err(error) => logger().error(&format!("{:#}", error)),
+    }
+}
+
 struct UserInputEvent {
     key: KeyCode,
     modifier: KeyModifiers,
@@ -99,6 +139,32 @@ fn get_handled_events() -> Vec<UserInputEvent> {
                 ui.set_current_cursor_offset(max_scroll_offset);
             }),
         },
+        UserInputEvent {
+            key: KeyCode::Down,
+            modifier: KeyModifiers::NONE,
+            on_trigger: Box::new(|| select_next_row(get_enabled_tasks().len())),
+        },
+        UserInputEvent {
+            key: KeyCode::Up,
+            modifier: KeyModifiers::NONE,
+            on_trigger: Box::new(|| select_previous_row(get_enabled_tasks().len())),
+        },
+        UserInputEvent {
+            key: KeyCode::Char('r'),
+            modifier: KeyModifiers::NONE,
+            on_trigger: Box::new(|| run_selected_task_now(false)),
+        },
+        // Same as above, but also makes the schedule start counting from now.
+        UserInputEvent {
+            key: KeyCode::Char('R'),
+            modifier: KeyModifiers::SHIFT,
+            on_trigger: Box::new(|| run_selected_task_now(true)),
+        },
+        UserInputEvent {
+            key: KeyCode::Char('p'),
+            modifier: KeyModifiers::NONE,
+            on_trigger: Box::new(toggle_selected_task_pause),
+        },
     ]
 }
 
@@ -1,4 +1,5 @@
 use crate::environment::{environment, types::RunningEnvironment};
+use crate::utils::clock::SystemClock;
 use crate::utils::time::get_current_formatted_date;
 use colored::Colorize;
 
@@ -48,7 +49,7 @@ impl LogBuilder {
     }
 
     pub fn add_date(&mut self) -> Self {
-        let current_formatted_date = get_current_formatted_date();
+        let current_formatted_date = get_current_formatted_date(&SystemClock);
         let colored_current_formatted_date = current_formatted_date.white().to_string();
         self.date = Some(colored_current_formatted_date);
         self.clone()
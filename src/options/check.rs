@@ -0,0 +1,67 @@
+use super::CommandOption;
+use crate::api_wrappers::storage::{get_storage_backend, get_storage_backend_names};
+use crate::environment;
+use crate::environment::utils::validation::{describe_problems, validate_configuration};
+use crate::init_command_option;
+use crate::utils::exit::exit_with_failure;
+use crate::utils::startup::install_crypto_ring_default_provider;
+use std::time::Duration;
+
+// Unlike the configuration, these are checked only by this option, as the scheduler retries tasks
+// failing because of temporary outages.
+async fn check_connections() -> Vec<String> {
+    let config = environment::environment();
+    let mut problems = vec![];
+
+    for backend_name in get_storage_backend_names() {
+        println!("Checking the `{}` storage backend...", backend_name);
+        if let Err(error) = get_storage_backend(&backend_name).await {
+            problems.push(format!("{:#}", error));
+        }
+    }
+
+    if config.letterboxd.backup_enable {
+        let status_url = format!("{}:{}/status", config.webdriver.url, config.webdriver.port);
+        println!("Checking WebDriver ({})...", status_url);
+        let response = reqwest::Client::new()
+            .get(&status_url)
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(error) = response {
+            problems.push(format!(
+                "Can't reach WebDriver at {}! Make sure that it's running. ({})",
+                status_url, error
+            ));
+        }
+    }
+
+    problems
+}
+
+async fn callback() {
+    install_crypto_ring_default_provider();
+
+    println!("Checking the configuration...");
+    let mut problems = validate_configuration().await;
+    // Connections can't be checked without knowing where to connect.
+    if problems.is_empty() {
+        problems.extend(check_connections().await);
+    }
+
+    if !problems.is_empty() {
+        println!("{}", describe_problems(&problems));
+        exit_with_failure();
+        return;
+    }
+    println!("No problems found.");
+}
+
+init_command_option!(
+    vec!["check"],
+    "Loads the whole configuration and reports every problem with it (missing or invalid values, secrets which can't be decrypted, unreachable storage backends or WebDriver) at once. Usage: `$ asmda check`.",
+    callback,
+    // Validates the configuration on its own, along with the connections.
+    false
+);
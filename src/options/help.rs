@@ -1,5 +1,5 @@
 use super::CommandOption;
-use crate::environment::environment;
+use crate::environment::utils::generic::get_program_version;
 use crate::utils::types::AsyncOutput;
 
 static HELP_MESSAGE_HEADER: &str = "
@@ -10,7 +10,7 @@ $ asmda [option]
 fn get_help_message_footer() -> String {
     format!(
         "\n\nASMDA v{} by Khenzii <khenzii@khenzii.dev>",
-        environment().metadata.program_version
+        get_program_version()
     )
 }
 
@@ -57,5 +57,6 @@ pub fn get_option(context: &[CommandOption]) -> CommandOption {
         string_identifiers: self_string_identifiers,
         description: self_description,
         callback,
+        needs_environment: false,
     }
 }
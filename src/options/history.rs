@@ -0,0 +1,86 @@
+use super::{CommandOption, get_option_arguments};
+use crate::api_wrappers::database::DatabaseClient;
+use crate::api_wrappers::database::types::RunRecord;
+use crate::api_wrappers::storage::utils::format_size;
+use crate::init_command_option;
+use crate::tui::utils::get_centered_cell_from_string;
+use crate::utils::time::format_system_time;
+use comfy_table::Table as ComfyTable;
+use comfy_table::modifiers::{UTF8_ROUND_CORNERS, UTF8_SOLID_INNER_BORDERS};
+use comfy_table::presets::UTF8_FULL;
+
+// The history is kept forever, so only the most recent runs are printed.
+static AMOUNT_OF_PRINTED_RUNS: u64 = 50;
+
+fn format_duration(run: &RunRecord) -> String {
+    match run.finished_at {
+        Some(finished_at) => {
+            let duration = finished_at
+                .duration_since(run.started_at)
+                .unwrap_or_default();
+            format!("{}s", duration.as_secs())
+        }
+        None => "-".to_string(),
+    }
+}
+
+fn print_runs(runs: &[RunRecord]) {
+    let mut table = ComfyTable::new();
+    table.set_header(
+        [
+            "Task",
+            "Started at",
+            "Duration",
+            "Outcome",
+            "Uploaded",
+            "Storage path",
+            "Error",
+        ]
+        .map(get_centered_cell_from_string),
+    );
+    table.load_preset(UTF8_FULL);
+    table.apply_modifier(UTF8_ROUND_CORNERS);
+    table.apply_modifier(UTF8_SOLID_INNER_BORDERS);
+
+    for run in runs {
+        table.add_row(
+            [
+                run.app_name.clone(),
+                format_system_time(run.started_at),
+                format_duration(run),
+                run.outcome.as_str().to_string(),
+                run.bytes_uploaded
+                    .map(format_size)
+                    .unwrap_or_else(|| "-".to_string()),
+                run.storage_path.clone().unwrap_or_else(|| "-".to_string()),
+                run.error_message.clone().unwrap_or_else(|| "-".to_string()),
+            ]
+            .iter()
+            .map(|value| get_centered_cell_from_string(value)),
+        );
+    }
+
+    println!("{}", table);
+}
+
+async fn callback() {
+    let arguments = get_option_arguments();
+    let app_name = arguments.first().map(|name| name.trim_end_matches('/'));
+
+    let runs = DatabaseClient::new().get_runs(app_name, AMOUNT_OF_PRINTED_RUNS);
+    if runs.is_empty() {
+        match app_name {
+            Some(app_name) => println!("No runs of `{}` have been recorded yet.", app_name),
+            None => println!("No runs have been recorded yet."),
+        }
+        return;
+    }
+
+    print_runs(&runs);
+}
+
+init_command_option!(
+    vec!["history"],
+    "Prints recent runs of all tasks (or only the passed one), along with their outcomes, durations, errors and uploaded backups. Usage: `$ asmda history [<archiver>]`.",
+    callback
+);
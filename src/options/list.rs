@@ -0,0 +1,128 @@
+use super::{CommandOption, get_option_arguments};
+use crate::api_wrappers::database::DatabaseClient;
+use crate::api_wrappers::storage::catalogue::{CatalogueEntry, build_catalogue};
+use crate::api_wrappers::storage::list_backups_in_all_storage_backends;
+use crate::api_wrappers::storage::utils::format_size;
+use crate::init_command_option;
+use crate::tui::utils::get_centered_cell_from_string;
+use crate::utils::constants::ArchiverIdentificator;
+use comfy_table::Table as ComfyTable;
+use comfy_table::modifiers::{UTF8_ROUND_CORNERS, UTF8_SOLID_INNER_BORDERS};
+use comfy_table::presets::UTF8_FULL;
+use serde_json::json;
+
+fn print_as_table(entries: &[CatalogueEntry]) {
+    let mut table = ComfyTable::new();
+    table.set_header(
+        [
+            "Archiver",
+            "Timestamp",
+            "Size",
+            "Content hash",
+            "Destination",
+            "State",
+        ]
+        .map(get_centered_cell_from_string),
+    );
+    table.load_preset(UTF8_FULL);
+    table.apply_modifier(UTF8_ROUND_CORNERS);
+    table.apply_modifier(UTF8_SOLID_INNER_BORDERS);
+
+    for entry in entries {
+        // Full hashes are too long to fit in a terminal, and the prefix is enough to compare them.
+        let content_hash = match &entry.content_hash {
+            Some(hash) => hash.chars().take(12).collect(),
+            None => "-".to_string(),
+        };
+        let destinations = if entry.destinations.is_empty() {
+            "-".to_string()
+        } else {
+            entry.destinations.join(", ")
+        };
+
+        table.add_row(
+            [
+                entry.archiver.clone(),
+                entry.timestamp.clone(),
+                format_size(entry.size),
+                content_hash,
+                destinations,
+                entry.state.as_str().to_string(),
+            ]
+            .iter()
+            .map(|value| get_centered_cell_from_string(value)),
+        );
+    }
+
+    println!("{}", table);
+}
+
+fn print_as_json(entries: &[CatalogueEntry]) {
+    let values: Vec<serde_json::Value> = entries
+        .iter()
+        .map(|entry| {
+            json!({
+                "archiver": entry.archiver,
+                "path": entry.directory,
+                "timestamp": entry.timestamp,
+                "size": entry.size,
+                "content_hash": entry.content_hash,
+                "destinations": entry.destinations,
+                "state": entry.state.as_str(),
+            })
+        })
+        .collect();
+
+    println!("{}", serde_json::to_string_pretty(&values).unwrap());
+}
+
+async fn callback() {
+    let arguments = get_option_arguments();
+    let is_json = arguments.iter().any(|argument| argument == "--json");
+    let archiver_names: Vec<String> = match arguments.iter().find(|argument| *argument != "--json")
+    {
+        Some(name) => vec![name.trim_end_matches('/').to_string()],
+        None => ArchiverIdentificator::get_all()
+            .iter()
+            .map(|identificator| identificator.as_str())
+            .collect(),
+    };
+
+    let records = DatabaseClient::new().get_stored_backups();
+
+    let mut entries = Vec::new();
+    for archiver_name in archiver_names {
+        let (listings, failed) =
+            list_backups_in_all_storage_backends(&format!("{}/", archiver_name)).await;
+        // Printed to stderr, so that the JSON output stays valid.
+        for (name, error) in &failed {
+            eprintln!(
+                "Failed to list backups in `{}`, they might be reported as missing! Details: {:#}",
+                name, error
+            );
+        }
+
+        let archiver_records: Vec<_> = records
+            .iter()
+            .filter(|record| record.app_name == archiver_name)
+            .cloned()
+            .collect();
+        entries.extend(build_catalogue(
+            &archiver_name,
+            &listings,
+            &archiver_records,
+        ));
+    }
+
+    if is_json {
+        print_as_json(&entries);
+    } else {
+        print_as_table(&entries);
+    }
+}
+
+init_command_option!(
+    vec!["list"],
+    "Lists stored backups of all archivers (or only the passed one), along with their sizes, content hashes and storage backends holding them. Backups found only in storage or only in the database are flagged. Usage: `$ asmda list [<archiver>] [--json]`.",
+    callback
+);
@@ -21,5 +21,6 @@ init_command_option!(
         "Shows latest logs generated by the program. Equivalent to running `$ cat {}`.",
         get_log_path_string()
     ),
-    callback
+    callback,
+    false
 );
@@ -1,10 +1,20 @@
+pub mod check;
 pub mod encrypt;
 pub mod help;
+pub mod history;
+pub mod list;
 pub mod logs;
 pub mod macros;
+pub mod pause;
+pub mod restore;
+pub mod resume;
 pub mod run;
+pub mod run_now;
+pub mod verify;
 pub mod version;
 
+use crate::environment::utils::validation::{describe_problems, validate_configuration};
+use crate::utils::exit::exit_with_failure;
 use crate::utils::types::AsyncFn;
 use std::env;
 
@@ -12,6 +22,13 @@ pub struct CommandOption {
     string_identifiers: Vec<String>,
     callback: AsyncFn,
     description: String,
+    // Whether the callback builds the environment, which has to be validated beforehand.
+    needs_environment: bool,
+}
+
+// Arguments passed after the option itself, e.g. `["letterboxd"]` for `$ asmda restore letterboxd`.
+pub fn get_option_arguments() -> Vec<String> {
+    env::args().skip(2).collect()
 }
 
 pub struct OptionsHandler {
@@ -31,6 +48,14 @@ impl OptionsHandler {
             logs::get_option(),
             version::get_option(),
             encrypt::get_option(),
+            restore::get_option(),
+            list::get_option(),
+            verify::get_option(),
+            history::get_option(),
+            run_now::get_option(),
+            pause::get_option(),
+            resume::get_option(),
+            check::get_option(),
         ];
         let help_option = help::get_option(&options);
         options.push(help_option);
@@ -38,13 +63,35 @@ impl OptionsHandler {
         OptionsHandler { options }
     }
 
+    // Problems are reported before the option runs, as building the environment panics on the first
+    // one.
+    async fn is_configuration_valid() -> bool {
+        let problems = validate_configuration().await;
+        if problems.is_empty() {
+            return true;
+        }
+
+        println!("{}", describe_problems(&problems));
+        println!("Run `$ asmda check` after fixing them.");
+        exit_with_failure();
+        false
+    }
+
+    async fn dispatch(option: &CommandOption) {
+        if option.needs_environment && !Self::is_configuration_valid().await {
+            return;
+        }
+
+        let handler = &option.callback;
+        handler().await;
+    }
+
     pub async fn handle(&self) {
         let args: Vec<String> = env::args().collect();
 
         // No options passed.
         if args.len() == 1 {
-            let run_callback = run::get_option().callback;
-            return run_callback().await;
+            return Self::dispatch(&run::get_option()).await;
         }
 
         let passed_string = args[1].clone();
@@ -53,8 +100,7 @@ impl OptionsHandler {
                 continue;
             };
 
-            let handler = &supported_option.callback;
-            handler().await;
+            Self::dispatch(supported_option).await;
             return;
         }
 
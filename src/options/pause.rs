@@ -0,0 +1,26 @@
+use super::{CommandOption, get_option_arguments};
+use crate::init_command_option;
+use crate::schedule::tasks::set_task_paused;
+use crate::utils::exit::exit_with_failure;
+
+async fn callback() {
+    let arguments = get_option_arguments();
+    let Some(app_name) = arguments.first() else {
+        println!("Archiver has not been passed! Usage: $ asmda pause <archiver>");
+        exit_with_failure();
+        return;
+    };
+
+    if let Err(error) = set_task_paused(app_name, true) {
+        println!("{:#}", error);
+        exit_with_failure();
+        return;
+    }
+    println!("Paused `{}` task.", app_name);
+}
+
+init_command_option!(
+    vec!["pause"],
+    "Pauses the passed task, so that the running ASMDA instance doesn't run it (even after restarting) until it's resumed. A run that is already in progress is finished, but not retried. Usage: `$ asmda pause <archiver>`.",
+    callback
+);
@@ -0,0 +1,141 @@
+use super::{CommandOption, get_option_arguments};
+use crate::api_wrappers::storage::encryption::{decrypt_backup, is_encrypted_backup};
+use crate::api_wrappers::storage::retention::StoredBackup;
+use crate::api_wrappers::storage::utils::format_size;
+use crate::api_wrappers::storage::{
+    BackendListing, download_to_temporary_file, get_storage_backend,
+    list_backups_in_all_storage_backends,
+};
+use crate::init_command_option;
+use crate::utils::exit::exit_with_failure;
+use crate::utils::time::format_path_friendly_date;
+use anyhow::{Context, Result, bail};
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+static USAGE: &str = "Usage: $ asmda restore <archiver> [<timestamp>/latest] [<output path>]";
+
+// Prints every backup once, along with the backends holding it.
+fn print_backups(archiver_name: &str, listings: &[BackendListing]) {
+    let mut backups: Vec<(&StoredBackup, Vec<&str>)> = Vec::new();
+    for listing in listings {
+        for backup in &listing.backups {
+            match backups
+                .iter_mut()
+                .find(|(other, _)| other.directory == backup.directory)
+            {
+                Some((_, backend_names)) => backend_names.push(&listing.backend_name),
+                None => backups.push((backup, vec![&listing.backend_name])),
+            }
+        }
+    }
+    backups.sort_by_key(|(backup, _)| std::cmp::Reverse(backup.created_at));
+
+    if backups.is_empty() {
+        println!("No backups of `{}` have been found.", archiver_name);
+        return;
+    }
+
+    println!("Available backups of `{}` (newest first):", archiver_name);
+    for (backup, backend_names) in backups {
+        let size: u64 = backup.objects.iter().map(|object| object.size).sum();
+        println!(
+            "{} - {} - {}",
+            format_path_friendly_date(&backup.created_at),
+            format_size(size),
+            backend_names.join(", ")
+        );
+    }
+}
+
+// Finds the requested backup, preferring backends in the order they are configured in.
+fn find_backup<'a>(
+    listings: &'a [BackendListing],
+    selection: &str,
+) -> Option<(&'a str, &'a StoredBackup)> {
+    let mut candidates = listings.iter().flat_map(|listing| {
+        listing
+            .backups
+            .iter()
+            .map(|backup| (listing.backend_name.as_str(), backup))
+    });
+
+    if selection == "latest" {
+        // Contrary to `max_by_key`, returns the first of equally new backups.
+        return candidates.min_by_key(|(_, backup)| std::cmp::Reverse(backup.created_at));
+    }
+
+    candidates.find(|(_, backup)| format_path_friendly_date(&backup.created_at) == selection)
+}
+
+async fn restore(arguments: &[String]) -> Result<()> {
+    let Some(archiver_name) = arguments.first() else {
+        bail!("Archiver has not been passed! {}", USAGE);
+    };
+    let prefix = format!("{}/", archiver_name.trim_end_matches('/'));
+
+    let (listings, failed) = list_backups_in_all_storage_backends(&prefix).await;
+    for (name, error) in &failed {
+        println!("Failed to list backups in `{}`! Details: {:#}", name, error);
+    }
+
+    let Some(selection) = arguments.get(1) else {
+        print_backups(archiver_name, &listings);
+        return Ok(());
+    };
+
+    let (backend_name, backup) = find_backup(&listings, selection).with_context(|| {
+        format!(
+            "Backup `{}` of `{}` has not been found! Run `$ asmda restore {}` to list available ones.",
+            selection, archiver_name, archiver_name
+        )
+    })?;
+    let object = backup
+        .objects
+        .first()
+        .context("The backup doesn't contain any files!")?;
+
+    let backend = get_storage_backend(backend_name).await?;
+    let mut payload = download_to_temporary_file(backend.as_ref(), &object.path).await?;
+    if is_encrypted_backup(&object.path) {
+        payload = decrypt_backup(payload)
+            .await
+            .context("Failed to decrypt the backup! Is `SECRETS_DECRYPTION_KEY` the right key?")?;
+    }
+
+    let output_path = match arguments.get(2) {
+        Some(path) => path.clone(),
+        None => format!(
+            "{}-{}.zip",
+            archiver_name.replace('/', "-"),
+            backup.created_at.format("%Y-%m-%dT%H-%M-%S")
+        ),
+    };
+    let mut output = File::create(&output_path)
+        .await
+        .with_context(|| format!("Failed to create {}!", output_path))?;
+    tokio::io::copy(&mut payload.open().await?, &mut output)
+        .await
+        .with_context(|| format!("Failed to write {}!", output_path))?;
+    output.flush().await?;
+
+    println!(
+        "Restored `{}` from `{}` to {}.",
+        backup.directory, backend_name, output_path
+    );
+    Ok(())
+}
+
+async fn callback() {
+    let arguments = get_option_arguments();
+    if let Err(error) = restore(&arguments).await {
+        println!("{:#}", error);
+        exit_with_failure();
+    }
+}
+
+init_command_option!(
+    vec!["restore", "fetch"],
+    "Lists backups of an archiver stored in the configured storage backends, or downloads the chosen one (or the latest) to a local path, decrypting it if needed. Usage: `$ asmda restore <archiver> [<timestamp>/latest] [<output path>]`.",
+    callback
+);
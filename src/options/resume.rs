@@ -0,0 +1,26 @@
+use super::{CommandOption, get_option_arguments};
+use crate::init_command_option;
+use crate::schedule::tasks::set_task_paused;
+use crate::utils::exit::exit_with_failure;
+
+async fn callback() {
+    let arguments = get_option_arguments();
+    let Some(app_name) = arguments.first() else {
+        println!("Archiver has not been passed! Usage: $ asmda resume <archiver>");
+        exit_with_failure();
+        return;
+    };
+
+    if let Err(error) = set_task_paused(app_name, false) {
+        println!("{:#}", error);
+        exit_with_failure();
+        return;
+    }
+    println!("Resumed `{}` task.", app_name);
+}
+
+init_command_option!(
+    vec!["resume"],
+    "Resumes the passed task, previously paused from the TUI or with the `pause` option. Usage: `$ asmda resume <archiver>`.",
+    callback
+);
@@ -1,11 +1,13 @@
 use super::CommandOption;
+use crate::environment::reload_environment;
 use crate::init_command_option;
 use crate::logger::logger;
 use crate::schedule::Scheduler;
 use crate::schedule::tasks::get_enabled_tasks;
+use crate::signals::wait_for_reload_request;
 use crate::tui::table::utils::convert_tasks_to_thread_safe_task_data;
 use crate::utils::{startup::startup, terminal::setup_tui};
-use std::{sync::Arc, thread};
+use std::sync::Arc;
 
 async fn callback() {
     setup_tui(Some(Arc::new(Box::new(|| {
@@ -18,7 +20,19 @@ async fn callback() {
     let mut scheduler = Scheduler::new(None);
     scheduler.run().await;
 
-    thread::park();
+    loop {
+        wait_for_reload_request().await;
+        if let Err(error) = reload_environment().await {
+            logger().error(&format!(
+                "Failed to reload the configuration, the current one is kept! {:#}",
+                error
+            ));
+            continue;
+        }
+
+        scheduler.reload(get_enabled_tasks()).await;
+        logger().log("Reloaded the configuration.");
+    }
 }
 
 init_command_option!(
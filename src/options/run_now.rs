@@ -0,0 +1,35 @@
+use super::{CommandOption, get_option_arguments};
+use crate::init_command_option;
+use crate::schedule::tasks::request_run_now;
+use crate::utils::exit::exit_with_failure;
+
+static USAGE: &str = "Usage: $ asmda run-now <archiver> [--reschedule]";
+
+async fn callback() {
+    let arguments = get_option_arguments();
+    let reschedule = arguments.iter().any(|argument| argument == "--reschedule");
+    let Some(app_name) = arguments
+        .iter()
+        .find(|argument| *argument != "--reschedule")
+    else {
+        println!("Archiver has not been passed! {}", USAGE);
+        exit_with_failure();
+        return;
+    };
+
+    if let Err(error) = request_run_now(app_name, reschedule) {
+        println!("{:#}", error);
+        exit_with_failure();
+        return;
+    }
+    println!(
+        "Requested running `{}` task. The running ASMDA instance will start it within a second (or right after starting, if it isn't running).",
+        app_name
+    );
+}
+
+init_command_option!(
+    vec!["run-now"],
+    "Asks the running ASMDA instance to run the passed task right away. The regular schedule isn't affected, unless `--reschedule` is passed, in which case it starts counting from this run. Usage: `$ asmda run-now <archiver> [--reschedule]`.",
+    callback
+);
@@ -0,0 +1,54 @@
+use super::{CommandOption, get_option_arguments};
+use crate::api_wrappers::database::DatabaseClient;
+use crate::api_wrappers::storage::verification::{VerificationStatus, verify_backups};
+use crate::init_command_option;
+use crate::utils::constants::ArchiverIdentificator;
+use crate::utils::exit::exit_with_failure;
+
+async fn callback() {
+    let arguments = get_option_arguments();
+    let archiver_names: Vec<String> = match arguments.first() {
+        Some(name) => vec![name.trim_end_matches('/').to_string()],
+        None => ArchiverIdentificator::get_all()
+            .iter()
+            .map(|identificator| identificator.as_str())
+            .collect(),
+    };
+    let records = DatabaseClient::new().get_stored_backups();
+
+    let mut amount_of_problems = 0;
+    for archiver_name in archiver_names {
+        println!("Verifying backups of `{}`...", archiver_name);
+        let report = verify_backups(&archiver_name, &records).await;
+
+        for result in &report.results {
+            if let (VerificationStatus::Valid(amount_of_entries), Some(backend_name)) =
+                (&result.status, &result.backend_name)
+            {
+                println!(
+                    "`{}` in `{}` is valid ({} entries).",
+                    result.directory, backend_name, amount_of_entries
+                );
+            }
+        }
+        for description in report.get_problems_descriptions() {
+            println!("{}", description);
+        }
+        amount_of_problems += report.get_amount_of_problems();
+    }
+
+    if amount_of_problems > 0 {
+        println!(
+            "Found {} problem(s) with stored backups!",
+            amount_of_problems
+        );
+        exit_with_failure();
+    }
+    println!("No problems found.");
+}
+
+init_command_option!(
+    vec!["verify"],
+    "Downloads stored backups of all archivers (or only the passed one), and checks whether they can be opened and match checksums recorded when they were uploaded. Usage: `$ asmda verify [<archiver>]`.",
+    callback
+);
@@ -1,11 +1,170 @@
 pub mod tasks;
 
+use crate::environment::environment;
 use crate::logger::logger;
+use crate::utils::clock::{SharedClock, system_clock};
+use crate::utils::time::format_system_time;
+use std::collections::HashMap;
 use std::mem::take;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tasks::utils::runtime_status::set_runtime_status;
+use tasks::utils::types::TaskStatus;
 use tasks::{Task, get_enabled_tasks};
+use tokio::sync::{Mutex as TokioMutex, OwnedMutexGuard, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+
+// How often the Scheduler checks whether running any of the tasks right away has been requested,
+// and whether paused ones have been resumed.
+static SCHEDULER_POLLING_RATE: Duration = Duration::from_secs(1);
+
+type ResourceLocks = HashMap<&'static str, Arc<TokioMutex<()>>>;
 
 pub struct Scheduler {
     tasks: Vec<Task>,
+    // Limits the amount of tasks running at the same time, `None` means no limit.
+    concurrency_limit: Option<Arc<Semaphore>>,
+    // The `SCHEDULER_MAX_CONCURRENT_TASKS` value the limit has been last set from.
+    configured_concurrency_limit: u64,
+    resource_locks: ResourceLocks,
+    clock: SharedClock,
+    // Loops of the spawned tasks, along with their app names.
+    task_loops: Vec<(String, JoinHandle<()>)>,
+    // Set once the spawned tasks are replaced, see `reload`.
+    are_tasks_replaced: Arc<AtomicBool>,
+}
+
+enum WakeUpReason {
+    Due,
+    // Running right away has been requested, `reschedule` tells whether the schedule should be
+    // reset.
+    Requested { reschedule: bool },
+    Replaced,
+}
+
+// Waits until the task is due, or has been requested to run right away. Paused tasks wait until
+// they are resumed. Replaced tasks stop waiting.
+async fn wait_until_due(
+    task: &mut Task,
+    clock: &SharedClock,
+    is_replaced: &AtomicBool,
+) -> WakeUpReason {
+    let mut has_logged_pause = false;
+    loop {
+        if is_replaced.load(Ordering::SeqCst) {
+            return WakeUpReason::Replaced;
+        }
+
+        if task.is_paused() {
+            if !has_logged_pause && task.get_time_until_next_run().is_zero() {
+                logger().log(&format!(
+                    "{} task is paused, it will run once resumed.",
+                    task.get_app_name().as_str()
+                ));
+                has_logged_pause = true;
+            }
+            clock.sleep(SCHEDULER_POLLING_RATE).await;
+            continue;
+        }
+
+        if let Some(request) = task.take_run_request() {
+            return WakeUpReason::Requested {
+                reschedule: request.reschedule,
+            };
+        }
+
+        let time_until_next_run = task.get_time_until_next_run();
+        if time_until_next_run.is_zero() {
+            if !task.skip_missed_runs() {
+                return WakeUpReason::Due;
+            }
+            logger().log(&format!(
+                "Skipped missed runs of {} task, the next one is at {}.",
+                task.get_app_name().as_str(),
+                format_system_time(task.get_next_run())
+            ));
+            continue;
+        }
+        clock
+            .sleep(time_until_next_run.min(SCHEDULER_POLLING_RATE))
+            .await;
+    }
+}
+
+// Waits until the task can run, returning everything that has to be held while it's running.
+// Resource locks are taken before the concurrency slot, so that a task waiting for a lock doesn't
+// block tasks not needing it. They are always taken in the same order, to avoid deadlocks.
+async fn wait_for_turn(
+    app_name: &str,
+    mut lock_names: Vec<&'static str>,
+    resource_locks: &ResourceLocks,
+    concurrency_limit: &Option<Arc<Semaphore>>,
+) -> (Vec<OwnedMutexGuard<()>>, Option<OwnedSemaphorePermit>) {
+    lock_names.sort();
+    lock_names.dedup();
+
+    let mut guards = Vec::new();
+    for name in lock_names {
+        let lock = resource_locks[name].clone();
+        let guard = match lock.clone().try_lock_owned() {
+            Ok(guard) => guard,
+            Err(_) => {
+                logger().log(&format!(
+                    "{} task is queued, waiting for the `{}` lock...",
+                    app_name, name
+                ));
+                lock.lock_owned().await
+            }
+        };
+        guards.push(guard);
+    }
+
+    let permit = match concurrency_limit {
+        Some(semaphore) => Some(match semaphore.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                logger().log(&format!(
+                    "{} task is queued, too many tasks are already running...",
+                    app_name
+                ));
+                semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("The concurrency limit has been closed!")
+            }
+        }),
+        None => None,
+    };
+
+    (guards, permit)
+}
+
+// Everything a task has to hold while it's running, see `wait_for_turn`.
+type Turn = (Vec<OwnedMutexGuard<()>>, Option<OwnedSemaphorePermit>);
+
+// Lets a task wait for its turn to run. It's released between retry attempts, so that a failing
+// task doesn't block the others while it's waiting to retry.
+#[derive(Clone)]
+struct TurnQueue {
+    app_name: String,
+    lock_names: Vec<&'static str>,
+    resource_locks: ResourceLocks,
+    concurrency_limit: Option<Arc<Semaphore>>,
+}
+
+impl TurnQueue {
+    async fn wait(&self) -> Turn {
+        set_runtime_status(&self.app_name, Some(TaskStatus::Queued));
+        wait_for_turn(
+            &self.app_name,
+            self.lock_names.clone(),
+            &self.resource_locks,
+            &self.concurrency_limit,
+        )
+        .await
+    }
 }
 
 impl Scheduler {
@@ -16,26 +175,107 @@ impl Scheduler {
             None => get_enabled_tasks(),
         };
 
-        Self { tasks }
+        let configured_concurrency_limit = environment().scheduler.max_concurrent_tasks;
+        let mut scheduler = Self {
+            tasks,
+            concurrency_limit: None,
+            configured_concurrency_limit,
+            resource_locks: HashMap::new(),
+            clock: system_clock(),
+            task_loops: Vec::new(),
+            are_tasks_replaced: Arc::new(AtomicBool::new(false)),
+        };
+        scheduler.set_concurrency_limit(configured_concurrency_limit as usize);
+        scheduler
+    }
+
+    // 0 means no limit.
+    pub fn set_concurrency_limit(&mut self, limit: usize) {
+        self.concurrency_limit = match limit {
+            0 => None,
+            limit => Some(Arc::new(Semaphore::new(limit))),
+        };
+    }
+
+    // Used only for waiting between runs, tasks have their own clocks (see `Task::with_clock`).
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
     }
 
     pub async fn run(&mut self) {
         let tasks = take(&mut self.tasks);
+        for task in &tasks {
+            for name in task.get_resource_locks() {
+                self.resource_locks.entry(name).or_default();
+            }
+        }
+
+        let mut previous_task_loops = take(&mut self.task_loops);
         for mut task in tasks {
-            tokio::spawn(async move {
-                loop {
-                    let time_until_next_run = task.get_time_until_next_run();
-                    let app_name = task.get_app_name();
+            let app_name = task.get_app_name();
+            let app_name_string = app_name.as_str();
+            task.set_turn_queue(TurnQueue {
+                app_name: app_name_string.clone(),
+                lock_names: task.get_resource_locks(),
+                resource_locks: self.resource_locks.clone(),
+                concurrency_limit: self.concurrency_limit.clone(),
+            });
+            let clock = self.clock.clone();
+            let is_replaced = self.are_tasks_replaced.clone();
+            // Replaced tasks with the same app name finish their runs before this one starts.
+            let (replaced_task_loops, other_task_loops) = previous_task_loops
+                .into_iter()
+                .partition(|(name, _)| *name == app_name_string);
+            previous_task_loops = other_task_loops;
 
-                    tokio::time::sleep(time_until_next_run).await;
+            let task_loop = tokio::spawn(async move {
+                for (_, replaced_task_loop) in replaced_task_loops {
+                    let _ = replaced_task_loop.await;
+                }
 
-                    logger().log(&format!("Archiving {}...", app_name.as_str(),));
+                loop {
+                    let requested_reschedule =
+                        match wait_until_due(&mut task, &clock, &is_replaced).await {
+                            WakeUpReason::Due => None,
+                            WakeUpReason::Requested { reschedule } => Some(reschedule),
+                            WakeUpReason::Replaced => break,
+                        };
 
-                    task.run().await;
+                    let app_name_string = app_name.as_str();
+                    match requested_reschedule {
+                        Some(reschedule) => {
+                            logger()
+                                .log(&format!("Running {} task on request...", app_name_string));
+                            task.run_now(reschedule).await;
+                        }
+                        None => {
+                            logger().log(&format!("Running {} task...", app_name_string));
+                            task.run().await;
+                        }
+                    }
 
-                    logger().log(&format!("Finished archiving {}!", app_name.as_str(),));
+                    logger().log(&format!("Finished {} task!", app_name_string));
                 }
             });
+            self.task_loops.push((app_name_string, task_loop));
         }
     }
+
+    // Replaces the running tasks with `tasks`, e.g. rebuilt after the configuration has changed.
+    // Runs which are already in progress are finished first. Replaced tasks which are only waiting
+    // stop right away (or within `SCHEDULER_POLLING_RATE`).
+    pub async fn reload(&mut self, tasks: Vec<Task>) {
+        self.are_tasks_replaced.store(true, Ordering::SeqCst);
+        self.are_tasks_replaced = Arc::new(AtomicBool::new(false));
+
+        // Runs in progress keep their slots of the previous limit until they finish.
+        let configured_concurrency_limit = environment().scheduler.max_concurrent_tasks;
+        if configured_concurrency_limit != self.configured_concurrency_limit {
+            self.configured_concurrency_limit = configured_concurrency_limit;
+            self.set_concurrency_limit(configured_concurrency_limit as usize);
+        }
+
+        self.tasks = tasks;
+        self.run().await;
+    }
 }
@@ -1,41 +1,53 @@
-use crate::api_wrappers::s3::S3Client;
-use crate::api_wrappers::s3::utils::get_backup_path_for_archiver;
-use crate::archivers::InstantArchiver;
 use crate::archivers::letterboxd::LetterboxdArchiver;
+use crate::archivers::{Archiver, InstantArchiver};
 use crate::environment::environment;
-use crate::logger::logger;
+use crate::environment::types::LetterboxdAccount;
 use crate::schedule::tasks::Task;
 use crate::schedule::tasks::utils::types::TaskConfig;
-use crate::status::status_server;
-use crate::utils::constants::ArchiverIdentificator;
-use crate::{init_new_task, task_callback};
-use std::sync::Mutex;
-use std::time::Duration;
+use crate::schedule::tasks::utils::workflows::store_backup;
+use crate::utils::clock::SharedClock;
+use crate::utils::constants::{ArchiverIdentificator, WEBDRIVER_RESOURCE_LOCK};
+use anyhow::{Context, Result, bail};
 
-async fn callback() {
-    let letterboxd_archiver = LetterboxdArchiver {};
-    let data = letterboxd_archiver.get_data().await;
+async fn callback(account: LetterboxdAccount, clock: SharedClock) -> Result<()> {
+    let letterboxd_archiver = LetterboxdArchiver { account };
+    let app_name = letterboxd_archiver.get_identificator().as_str();
+    let data = letterboxd_archiver
+        .get_data()
+        .await
+        .with_context(|| format!("Failed to archive `{}`", app_name))?;
 
-    if let Err(error) = data {
-        let error_message = error.to_string();
-        logger().error(&error_message);
-
-        let new_status = Some("Failed to archive Letterboxd!".to_string());
-        status_server().set_error_message(new_status);
-    } else {
-        let s3 = S3Client::new().await;
-        s3.upload(
-            &get_backup_path_for_archiver(letterboxd_archiver),
-            "backup.zip",
-            data.unwrap(),
-        )
-        .await;
+    // Failures of specific storage backends have already been reported by `store_backup`.
+    if !store_backup(&letterboxd_archiver, data, &clock)
+        .await
+        .is_stored()
+    {
+        bail!("`{}` backup hasn't been stored anywhere", app_name);
     }
+    Ok(())
 }
 
-init_new_task!(TaskConfig {
-    callback: task_callback!(callback),
-    run_interval_seconds: environment().letterboxd.backup_frequency,
-    app_name: ArchiverIdentificator::Letterboxd,
-    is_enabled: environment().letterboxd.backup_enable,
-});
+// One task per account. They all share the schedule, but keep track of it separately.
+pub fn get_tasks() -> Vec<Task> {
+    let config = environment();
+    config
+        .letterboxd
+        .accounts
+        .iter()
+        .map(|account| {
+            let callback_account = account.clone();
+            Task::new(TaskConfig {
+                callback: Box::new(move |clock| {
+                    Box::pin(callback(callback_account.clone(), clock))
+                }),
+                schedule: config.letterboxd.backup_schedule.clone(),
+                catch_up_policy: config.letterboxd.backup_catch_up,
+                retry_policy: config.task_retry.clone(),
+                timeout_seconds: config.letterboxd.backup_timeout,
+                resource_locks: vec![WEBDRIVER_RESOURCE_LOCK],
+                app_name: ArchiverIdentificator::Letterboxd(account.name.clone()),
+                is_enabled: config.letterboxd.backup_enable,
+            })
+        })
+        .collect()
+}
@@ -1,68 +1,381 @@
 pub mod letterboxd;
 pub mod utils;
+pub mod verification;
 
+use crate::api_wrappers::browser::BrowserSessions;
 use crate::api_wrappers::database::DatabaseClient;
+use crate::api_wrappers::database::types::{BackupStatus, RunOutcome, RunRecord, RunRequest};
+use crate::environment::types::RetryPolicy;
+use crate::logger::logger;
+use crate::schedule::{Turn, TurnQueue};
+use crate::status::status_server;
+use crate::utils::clock::{SharedClock, system_clock};
 use crate::utils::constants::ArchiverIdentificator;
+use crate::utils::time::truncate_to_seconds;
+use anyhow::{Result, anyhow, bail};
+use std::sync::Mutex;
 use std::time::{Duration, SystemTime};
-use utils::types::ThreadCallback;
+use utils::retry::{RetryState, get_retry_delay};
+use utils::runtime_status::{get_runtime_status, set_runtime_status};
+use utils::schedule::{CatchUpPolicy, TaskSchedule};
+use utils::types::{TaskConfig, TaskStatus, ThreadCallback};
+use utils::workflows::report_archiving_error;
 
 pub struct Task {
     next_run: SystemTime,
-    interval: Duration,
+    schedule: TaskSchedule,
+    catch_up_policy: CatchUpPolicy,
+    retry_policy: RetryPolicy,
+    timeout: Option<Duration>,
+    resource_locks: Vec<&'static str>,
     callback: ThreadCallback,
     app_name: ArchiverIdentificator,
     database: DatabaseClient,
     is_enabled: bool,
+    clock: SharedClock,
+    // Set by the Scheduler. Without it, the task runs whenever asked to.
+    turn_queue: Option<TurnQueue>,
 }
 
 impl Task {
-    pub fn new(
-        interval: Duration,
-        callback: ThreadCallback,
-        app_name: ArchiverIdentificator,
-        is_enabled: bool,
-    ) -> Self {
-        let database = DatabaseClient::new();
-        let next_run = database.get_next_run_by_app_name(app_name.clone());
+    pub fn new(config: TaskConfig) -> Self {
+        Self::with_clock(config, system_clock())
+    }
+
+    // Useful mostly in tests, where a `FakeClock` can be passed.
+    pub fn with_clock(config: TaskConfig, clock: SharedClock) -> Self {
+        let database = DatabaseClient::new().with_clock(clock.clone());
+        let next_run = database
+            .get_next_run_by_app_name(config.app_name.clone())
+            .unwrap_or_else(|| config.schedule.get_first_run(clock.now()));
+
+        let timeout = match config.timeout_seconds {
+            0 => None,
+            seconds => Some(Duration::from_secs(seconds)),
+        };
 
         Self {
-            interval,
-            callback,
-            app_name,
+            schedule: config.schedule,
+            catch_up_policy: config.catch_up_policy,
+            retry_policy: config.retry_policy,
+            timeout,
+            resource_locks: config.resource_locks,
+            callback: Mutex::new(config.callback),
+            app_name: config.app_name,
             database,
             next_run,
-            is_enabled,
+            is_enabled: config.is_enabled,
+            clock,
+            turn_queue: None,
         }
     }
 
     pub fn get_time_until_next_run(&self) -> Duration {
-        let now = SystemTime::now();
-
-        match self.next_run.duration_since(now) {
+        match self.next_run.duration_since(self.clock.now()) {
             Ok(duration) => duration,
             Err(_) => Duration::from_secs(0), // We're already past the date.
         }
     }
 
+    pub(super) fn set_turn_queue(&mut self, turn_queue: TurnQueue) {
+        self.turn_queue = Some(turn_queue);
+    }
+
+    // Takes `&mut self` for the same reason as `run_callback`.
+    async fn wait_for_turn(&mut self) -> Option<Turn> {
+        let turn_queue = self.turn_queue.clone()?;
+        Some(turn_queue.wait().await)
+    }
+
+    // Runs the task on schedule, moving its next run to the following date. If runs have been
+    // missed, the date depends on the catch-up policy.
     pub async fn run(&mut self) {
-        self.next_run += self.interval;
+        let turn = self.wait_for_turn().await;
+        let (_, next_run) =
+            self.schedule
+                .catch_up(self.next_run, self.clock.now(), self.catch_up_policy);
+        self.next_run = next_run;
         self.database
             .update_next_run(self.app_name.clone(), self.next_run);
 
+        self.execute(turn).await;
+    }
+
+    // Moves the next run past now without running the task, if runs have been missed and the
+    // catch-up policy says to skip them. Returns whether that happened.
+    pub fn skip_missed_runs(&mut self) -> bool {
+        let (should_run, next_run) =
+            self.schedule
+                .catch_up(self.next_run, self.clock.now(), self.catch_up_policy);
+        if should_run {
+            return false;
+        }
+
+        self.next_run = next_run;
+        self.database
+            .update_next_run(self.app_name.clone(), self.next_run);
+        true
+    }
+
+    // Runs the task outside of its schedule. If `reschedule` is set, the schedule starts counting
+    // from now, otherwise it's left untouched.
+    pub async fn run_now(&mut self, reschedule: bool) {
+        let turn = self.wait_for_turn().await;
+        if reschedule {
+            self.next_run = self.schedule.get_next_run(self.clock.now());
+            self.database
+                .update_next_run(self.app_name.clone(), self.next_run);
+        }
+
+        self.execute(turn).await;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.database.is_task_paused(&self.app_name.as_str())
+    }
+
+    // Returns a pending "run now" request made from the TUI or the CLI, removing it.
+    pub fn take_run_request(&self) -> Option<RunRequest> {
+        self.database.take_run_request(&self.app_name.as_str())
+    }
+
+    // `turn` is held during the first attempt, each of the following ones waits for a new one.
+    async fn execute(&mut self, mut turn: Option<Turn>) {
+        let app_name = self.app_name.as_str();
+        let max_attempts = self.retry_policy.max_attempts.max(1);
+        let mut failed_attempts = 0;
+        loop {
+            if failed_attempts > 0 {
+                turn = self.wait_for_turn().await;
+            }
+            set_runtime_status(&app_name, Some(TaskStatus::Running));
+            let started_at = self.clock.now();
+            let run_id = self.database.start_run(&app_name, started_at);
+            let (outcome, result) = self.run_callback(&app_name).await;
+            let has_timed_out = outcome == RunOutcome::TimedOut;
+            self.finish_run(run_id, &app_name, started_at, outcome, &result);
+
+            drop(turn.take());
+
+            let Err(error) = result else {
+                break;
+            };
+            failed_attempts += 1;
+
+            let delay = get_retry_delay(&self.retry_policy, failed_attempts, rand::random());
+            let next_attempt = self.clock.now() + delay;
+            // There's no point retrying if the task would run anyway by then, or has been paused.
+            if failed_attempts >= max_attempts || next_attempt >= self.next_run || self.is_paused()
+            {
+                let mut status_message = format!(
+                    "{} task failed after {} attempt(s)!",
+                    app_name, failed_attempts
+                );
+                if has_timed_out {
+                    status_message.push_str(" The last one has timed out.");
+                }
+                report_archiving_error(error, status_message);
+                break;
+            }
+
+            logger().error(&format!(
+                "Attempt {}/{} of {} task failed, retrying in {}s! Details: {:#}",
+                failed_attempts,
+                max_attempts,
+                app_name,
+                delay.as_secs(),
+                error
+            ));
+            set_runtime_status(
+                &app_name,
+                Some(TaskStatus::Retrying(RetryState {
+                    failed_attempts,
+                    max_attempts,
+                    next_attempt,
+                })),
+            );
+            self.clock.sleep(delay).await;
+            // The task could have been paused while waiting.
+            if self.is_paused() {
+                report_archiving_error(
+                    error,
+                    format!(
+                        "{} task failed after {} attempt(s), and has been paused before retrying!",
+                        app_name, failed_attempts
+                    ),
+                );
+                break;
+            }
+            logger().log(&format!(
+                "Running attempt {}/{} of {} task...",
+                failed_attempts + 1,
+                max_attempts,
+                app_name
+            ));
+        }
+
+        set_runtime_status(&app_name, None);
+    }
+
+    // Runs the callback once, cancelling it if it exceeds the timeout. Browser sessions opened by a
+    // cancelled callback are closed, so that they don't block the WebDriver for the next runs. Takes
+    // `&mut self`, as `&Task` can't be held across awaits (the database connection isn't `Sync`).
+    async fn run_callback(&mut self, app_name: &str) -> (RunOutcome, Result<()>) {
         let future = {
             let mut callback = self.callback.lock().expect("Failed to access callback!");
-            (callback)()
+            (callback)(self.clock.clone())
         };
-        future.await;
+        let browser_sessions = BrowserSessions::new();
+
+        let Some(timeout) = self.timeout else {
+            return match browser_sessions.track(future).await {
+                Ok(()) => (RunOutcome::Succeeded, Ok(())),
+                Err(error) => (RunOutcome::Failed, Err(error)),
+            };
+        };
+
+        let timeout_expiry = self.clock.sleep(timeout);
+        let result = tokio::select! {
+            result = browser_sessions.track(future) => Some(result),
+            _ = timeout_expiry => None,
+        };
+
+        match result {
+            Some(Ok(())) => (RunOutcome::Succeeded, Ok(())),
+            Some(Err(error)) => (RunOutcome::Failed, Err(error)),
+            None => {
+                let message = format!("{} task timed out after {}s!", app_name, timeout.as_secs());
+                logger().error(&format!("{} Cancelling it...", message));
+                status_server().set_error_message(Some(message));
+
+                let amount_of_closed_sessions = browser_sessions.close_all().await;
+                if amount_of_closed_sessions > 0 {
+                    logger().log(&format!(
+                        "Closed {} browser session(s) of the cancelled {} task.",
+                        amount_of_closed_sessions, app_name
+                    ));
+                }
+
+                (
+                    RunOutcome::TimedOut,
+                    Err(anyhow!("Timed out after {}s", timeout.as_secs())),
+                )
+            }
+        }
+    }
+
+    // Saves the outcome of a single attempt in the run history. Backups are recorded separately by
+    // `store_backup`, so the one stored during the run (if any) is looked up. Its creation date is
+    // kept with a precision of a second, so the start of the run is compared at the same one.
+    fn finish_run(
+        &self,
+        run_id: i64,
+        app_name: &str,
+        started_at: SystemTime,
+        outcome: RunOutcome,
+        result: &Result<()>,
+    ) {
+        let backup = self
+            .database
+            .get_last_backup_record(app_name)
+            .filter(|backup| backup.created_at >= truncate_to_seconds(started_at));
+        let error_message = result.as_ref().err().map(|error| format!("{:#}", error));
+
+        self.database.finish_run(&RunRecord {
+            id: run_id,
+            app_name: app_name.to_string(),
+            started_at,
+            finished_at: Some(self.clock.now()),
+            outcome,
+            error_message,
+            bytes_uploaded: backup.as_ref().map(|backup| match backup.status {
+                BackupStatus::Stored | BackupStatus::Pruned => backup.size,
+                BackupStatus::Unchanged => 0,
+            }),
+            storage_path: backup.map(|backup| backup.path),
+        });
+    }
+
+    pub fn get_next_run(&self) -> SystemTime {
+        self.next_run
+    }
+
+    pub fn get_resource_locks(&self) -> Vec<&'static str> {
+        self.resource_locks.clone()
     }
 
     pub fn get_app_name(&self) -> ArchiverIdentificator {
         self.app_name.clone()
     }
+
+    // Status shown in the tasks table.
+    pub fn describe_status(&self) -> String {
+        self.get_status().as_str(self.clock.as_ref())
+    }
+
+    pub fn get_status(&self) -> TaskStatus {
+        if let Some(status) = get_runtime_status(&self.app_name.as_str()) {
+            return status;
+        }
+        if self.is_paused() {
+            return TaskStatus::Paused;
+        }
+
+        let pending_request = self.database.get_pending_request(self.app_name.clone());
+
+        match pending_request {
+            Some(_) => TaskStatus::WaitingForExport,
+            None => TaskStatus::Scheduled,
+        }
+    }
 }
 
 pub fn get_all_tasks() -> Vec<Task> {
-    vec![letterboxd::get_task()]
+    let mut tasks = letterboxd::get_tasks();
+    tasks.push(verification::get_task());
+    tasks
+}
+
+// Finds one of the tasks returned by `get_all_tasks` by its app name.
+fn find_task(app_name: &str) -> Result<Task> {
+    let tasks = get_all_tasks();
+    let names: Vec<String> = tasks
+        .iter()
+        .map(|task| task.get_app_name().as_str())
+        .collect();
+    let Some(task) = tasks
+        .into_iter()
+        .find(|task| task.get_app_name().as_str() == app_name)
+    else {
+        bail!(
+            "There's no `{}` task! Available ones: {}.",
+            app_name,
+            names.join(", ")
+        );
+    };
+    Ok(task)
+}
+
+// Asks the Scheduler (possibly running in another process) to run the task as soon as possible.
+pub fn request_run_now(app_name: &str, reschedule: bool) -> Result<()> {
+    let task = find_task(app_name)?;
+    if !task.is_enabled {
+        bail!("`{}` task is disabled, so it can't be run!", app_name);
+    }
+    if task.is_paused() {
+        bail!("`{}` task is paused, resume it first!", app_name);
+    }
+
+    task.database.add_run_request(app_name, reschedule);
+    Ok(())
+}
+
+// Paused tasks aren't run by the Scheduler (possibly running in another process) until resumed.
+// Runs which are already in progress are finished, but not retried.
+pub fn set_task_paused(app_name: &str, is_paused: bool) -> Result<()> {
+    let task = find_task(app_name)?;
+    task.database.set_task_paused(app_name, is_paused);
+    Ok(())
 }
 
 pub fn get_enabled_tasks() -> Vec<Task> {
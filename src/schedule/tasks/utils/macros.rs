@@ -2,23 +2,13 @@
 macro_rules! init_new_task {
     ($config:expr) => {
         pub fn get_task() -> Task {
-            Task::new(
-                Duration::from_secs($config.run_interval_seconds),
-                Mutex::new($config.callback),
-                $config.app_name,
-                $config.is_enabled,
-            )
+            Task::new($config)
         }
     };
 
     ($config:expr, $method_name:ident) => {
         pub fn $method_name() -> Task {
-            Task::new(
-                Duration::from_secs($config.run_interval_seconds),
-                Mutex::new($config.callback),
-                $config.app_name,
-                $config.is_enabled,
-            )
+            Task::new($config)
         }
     };
 }
@@ -26,6 +16,11 @@ macro_rules! init_new_task {
 #[macro_export]
 macro_rules! task_callback {
     ($func:path) => {
-        Box::new(|| Box::pin($func()))
+        Box::new(|_| Box::pin($func()))
+    };
+
+    // Passes the clock of the task to the callback.
+    ($func:path, clock) => {
+        Box::new(|clock| Box::pin($func(clock)))
     };
 }
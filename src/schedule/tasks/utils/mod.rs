@@ -1,2 +1,6 @@
 pub mod macros;
+pub mod retry;
+pub mod runtime_status;
+pub mod schedule;
 pub mod types;
+pub mod workflows;
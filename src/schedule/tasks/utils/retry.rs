@@ -0,0 +1,57 @@
+use crate::environment::types::RetryPolicy;
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryState {
+    pub failed_attempts: u64,
+    pub max_attempts: u64,
+    pub next_attempt: SystemTime,
+}
+
+// Computes how long to wait after `failed_attempts` attempts have failed. `random_factor` has to
+// be in the [0, 1) range, and decides where in the jitter range the delay lands.
+pub fn get_retry_delay(policy: &RetryPolicy, failed_attempts: u64, random_factor: f64) -> Duration {
+    let exponent = failed_attempts.saturating_sub(1).min(63) as u32;
+    let delay_seconds = policy
+        .initial_delay_seconds
+        .saturating_mul(2_u64.saturating_pow(exponent))
+        .min(policy.max_delay_seconds);
+
+    let jitter = (random_factor * 2.0 - 1.0) * policy.jitter_percentage as f64 / 100.0;
+    Duration::from_secs_f64((delay_seconds as f64 * (1.0 + jitter)).max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    mod get_retry_delay {
+        use super::super::*;
+
+        fn get_policy(jitter_percentage: u64) -> RetryPolicy {
+            RetryPolicy {
+                max_attempts: 5,
+                initial_delay_seconds: 60,
+                max_delay_seconds: 200,
+                jitter_percentage,
+            }
+        }
+
+        #[test]
+        fn doubles_the_delay_up_to_the_maximum() {
+            let policy = get_policy(0);
+            let delays: Vec<u64> = (1..=4)
+                .map(|attempt| get_retry_delay(&policy, attempt, 0.5).as_secs())
+                .collect();
+
+            assert_eq!(delays, vec![60, 120, 200, 200]);
+        }
+
+        #[test]
+        fn applies_jitter_in_both_directions() {
+            let policy = get_policy(50);
+
+            assert_eq!(get_retry_delay(&policy, 1, 0.0).as_secs(), 30);
+            assert_eq!(get_retry_delay(&policy, 1, 0.5).as_secs(), 60);
+            assert_eq!(get_retry_delay(&policy, 1, 0.75).as_secs(), 75);
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use super::types::TaskStatus;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// Tasks are recreated every time the tasks table is refreshed, so statuses which exist only while
+// the Scheduler is handling a task (e.g. queued or retrying) are kept here, by their app names.
+static RUNTIME_STATUSES: Lazy<Mutex<HashMap<String, TaskStatus>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn get_runtime_status(app_name: &str) -> Option<TaskStatus> {
+    let statuses = RUNTIME_STATUSES.lock().unwrap();
+    statuses.get(app_name).cloned()
+}
+
+// Passing `None` makes the task fall back to its persisted status.
+pub fn set_runtime_status(app_name: &str, status: Option<TaskStatus>) {
+    let mut statuses = RUNTIME_STATUSES.lock().unwrap();
+    match status {
+        Some(status) => statuses.insert(app_name.to_string(), status),
+        None => statuses.remove(app_name),
+    };
+}
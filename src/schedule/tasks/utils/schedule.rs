@@ -0,0 +1,199 @@
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Local, TimeZone};
+use chrono_tz::Tz;
+use croner::Cron;
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Debug)]
+pub enum TaskSchedule {
+    // Runs every N seconds, counting from the previous run.
+    Interval(Duration),
+    // Runs whenever the expression matches, e.g. `30 3 * * *` for every day at 03:30. `None` means
+    // the local timezone of the machine.
+    Cron(Box<Cron>, Option<Tz>),
+}
+
+// What happens when more than one run is overdue at once, e.g. because the machine was off.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CatchUpPolicy {
+    // Runs once for all missed runs, and then counts the schedule from now.
+    #[default]
+    RunOnce,
+    // Doesn't run at all, and waits for the first run after now.
+    Skip,
+    // Runs every missed run, one after another, until the schedule catches up with now.
+    RunAll,
+}
+
+impl CatchUpPolicy {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim() {
+            "once" => Ok(Self::RunOnce),
+            "skip" => Ok(Self::Skip),
+            "all" => Ok(Self::RunAll),
+            value => bail!(
+                "`{}` is not a valid catch-up policy! Use `once`, `skip` or `all`.",
+                value
+            ),
+        }
+    }
+}
+
+// Either `local` (returned as `None`) or an IANA name, like `Europe/Warsaw`.
+pub fn parse_timezone(value: &str) -> Result<Option<Tz>> {
+    match value.trim() {
+        "local" => Ok(None),
+        name => Ok(Some(
+            name.parse::<Tz>()
+                .map_err(|error| anyhow!(error))
+                .with_context(|| format!("`{}` is not a valid timezone!", name))?,
+        )),
+    }
+}
+
+fn find_next_occurrence<T: TimeZone>(cron: &Cron, after: DateTime<T>) -> Option<SystemTime> {
+    let occurrence = cron.find_next_occurrence(&after, false).ok()?;
+    Some(occurrence.into())
+}
+
+impl TaskSchedule {
+    // `value` is either an amount of seconds or a cron expression (5 fields, or 6 with seconds).
+    // `timezone` is either `local` or an IANA name, like `Europe/Warsaw`, and is used only by cron
+    // expressions.
+    pub fn parse(value: &str, timezone: &str) -> Result<Self> {
+        if let Ok(seconds) = value.trim().parse::<u64>() {
+            return Ok(Self::Interval(Duration::from_secs(seconds)));
+        }
+
+        let cron = Cron::new(value.trim())
+            .with_seconds_optional()
+            .parse()
+            .map_err(|error| anyhow!("{:?}", error))
+            .with_context(|| format!("`{}` is neither an integer nor a cron expression!", value))?;
+        Ok(Self::Cron(Box::new(cron), parse_timezone(timezone)?))
+    }
+
+    // Computes when the task should run after the run scheduled for `previous_run`.
+    pub fn get_next_run(&self, previous_run: SystemTime) -> SystemTime {
+        match self {
+            Self::Interval(interval) => previous_run + *interval,
+            Self::Cron(cron, timezone) => {
+                let next_run = match timezone {
+                    Some(timezone) => find_next_occurrence(
+                        cron,
+                        DateTime::<chrono::Utc>::from(previous_run).with_timezone(timezone),
+                    ),
+                    None => find_next_occurrence(cron, DateTime::<Local>::from(previous_run)),
+                };
+
+                // Expressions like `0 0 30 2 *` never match, so the task effectively never runs.
+                next_run.unwrap_or(SystemTime::UNIX_EPOCH + Duration::from_secs(u32::MAX as u64))
+            }
+        }
+    }
+
+    // Computes when a task that has never been scheduled before should run. Intervals start right
+    // away, while cron expressions wait for their first match, as they name specific times.
+    pub fn get_first_run(&self, now: SystemTime) -> SystemTime {
+        match self {
+            Self::Interval(_) => now,
+            Self::Cron(..) => self.get_next_run(now),
+        }
+    }
+
+    // Runs count as missed if the one following `scheduled_run` is overdue too, so a run which is
+    // merely late (e.g. queued behind other tasks) is never skipped.
+    pub fn has_missed_runs(&self, scheduled_run: SystemTime, now: SystemTime) -> bool {
+        self.get_next_run(scheduled_run) <= now
+    }
+
+    // Decides what happens to the run scheduled for `scheduled_run`, which is due at `now`. Returns
+    // whether it should run, and when the following one is.
+    pub fn catch_up(
+        &self,
+        scheduled_run: SystemTime,
+        now: SystemTime,
+        policy: CatchUpPolicy,
+    ) -> (bool, SystemTime) {
+        if policy == CatchUpPolicy::RunAll || !self.has_missed_runs(scheduled_run, now) {
+            return (true, self.get_next_run(scheduled_run));
+        }
+
+        (policy == CatchUpPolicy::RunOnce, self.get_next_run(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod task_schedule {
+        use super::super::*;
+        use chrono::Utc;
+
+        fn get_system_time(formatted_date: &str) -> SystemTime {
+            DateTime::parse_from_rfc3339(formatted_date)
+                .unwrap()
+                .with_timezone(&Utc)
+                .into()
+        }
+
+        #[test]
+        fn parses_intervals() {
+            let schedule = TaskSchedule::parse("60", "local").unwrap();
+            let previous_run = get_system_time("2025-01-01T00:00:00Z");
+
+            assert_eq!(
+                schedule.get_next_run(previous_run),
+                get_system_time("2025-01-01T00:01:00Z")
+            );
+        }
+
+        #[test]
+        fn computes_next_cron_run_in_timezone() {
+            let schedule = TaskSchedule::parse("30 3 * * *", "Europe/Warsaw").unwrap();
+            // 04:00 in Warsaw, so today's 03:30 has already passed.
+            let previous_run = get_system_time("2025-01-01T03:00:00Z");
+
+            assert_eq!(
+                schedule.get_next_run(previous_run),
+                get_system_time("2025-01-02T02:30:00Z")
+            );
+        }
+
+        #[test]
+        fn supports_weekdays() {
+            let schedule = TaskSchedule::parse("0 12 * * SUN", "UTC").unwrap();
+            // Wednesday.
+            let previous_run = get_system_time("2025-01-01T00:00:00Z");
+
+            assert_eq!(
+                schedule.get_next_run(previous_run),
+                get_system_time("2025-01-05T12:00:00Z")
+            );
+        }
+
+        #[test]
+        fn waits_for_first_cron_match() {
+            let now = get_system_time("2025-01-01T00:00:00Z");
+            let interval_schedule = TaskSchedule::parse("60", "UTC").unwrap();
+            let cron_schedule = TaskSchedule::parse("30 3 * * *", "UTC").unwrap();
+
+            assert_eq!(interval_schedule.get_first_run(now), now);
+            assert_eq!(
+                cron_schedule.get_first_run(now),
+                get_system_time("2025-01-01T03:30:00Z")
+            );
+        }
+
+        #[test]
+        fn rejects_invalid_values() {
+            assert!(TaskSchedule::parse("every day", "local").is_err());
+            assert!(TaskSchedule::parse("30 3 * * *", "Mars/Olympus").is_err());
+        }
+
+        #[test]
+        fn parses_catch_up_policies() {
+            assert_eq!(CatchUpPolicy::parse("skip").unwrap(), CatchUpPolicy::Skip);
+            assert!(CatchUpPolicy::parse("sometimes").is_err());
+        }
+    }
+}
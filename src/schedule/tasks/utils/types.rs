@@ -1,12 +1,63 @@
+use super::retry::RetryState;
+use super::schedule::{CatchUpPolicy, TaskSchedule};
+use crate::environment::types::RetryPolicy;
+use crate::utils::clock::Clock;
 use crate::utils::constants::ArchiverIdentificator;
 use crate::utils::types::AsyncFnMut;
 use std::sync::Mutex;
 
 pub struct TaskConfig {
-    pub run_interval_seconds: u64,
+    pub schedule: TaskSchedule,
+    pub catch_up_policy: CatchUpPolicy,
+    pub retry_policy: RetryPolicy,
+    // Runs taking longer are cancelled. 0 disables the timeout.
+    pub timeout_seconds: u64,
+    // Names of resources the task needs exclusive access to, e.g. `WEBDRIVER_RESOURCE_LOCK`. Tasks
+    // sharing a resource never run at the same time.
+    pub resource_locks: Vec<&'static str>,
     pub callback: AsyncFnMut,
     pub app_name: ArchiverIdentificator,
     pub is_enabled: bool,
 }
 
 pub type ThreadCallback = Mutex<AsyncFnMut>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TaskStatus {
+    Scheduled,
+    // Due, but waiting for a free slot or a resource lock held by another task.
+    Queued,
+    Running,
+    // Paused from the TUI or the CLI, won't run until resumed.
+    Paused,
+    // A `RequestArchiver` has requested the data, and is waiting for the platform to prepare it.
+    WaitingForExport,
+    // The last run has failed, and will be attempted again.
+    Retrying(RetryState),
+}
+
+impl TaskStatus {
+    // Countdowns are measured by `clock`, which should be the task's one.
+    pub fn as_str(&self, clock: &dyn Clock) -> String {
+        match self {
+            TaskStatus::Scheduled => "Scheduled".to_string(),
+            TaskStatus::Queued => "Queued".to_string(),
+            TaskStatus::Running => "Running".to_string(),
+            TaskStatus::Paused => "Paused".to_string(),
+            TaskStatus::WaitingForExport => "Waiting for export".to_string(),
+            TaskStatus::Retrying(state) => {
+                let seconds_left = state
+                    .next_attempt
+                    .duration_since(clock.now())
+                    .unwrap_or_default()
+                    .as_secs();
+                format!(
+                    "Retrying in {}s (attempt {}/{})",
+                    seconds_left,
+                    state.failed_attempts + 1,
+                    state.max_attempts
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use crate::api_wrappers::database::DatabaseClient;
+use crate::api_wrappers::database::types::{BackupRecord, BackupStatus, PendingRequest};
+use crate::api_wrappers::mail::types::{MailMessage, MailRule};
+use crate::api_wrappers::mail::{ImapClient, pick_unhandled_download_url};
+use crate::api_wrappers::storage::encryption::{encrypt_backup, get_backup_filename};
+use crate::api_wrappers::storage::retention::prune_backups;
+use crate::api_wrappers::storage::types::BackupPayload;
+use crate::api_wrappers::storage::utils::{
+    get_backup_path_for_archiver, get_backup_prefix_for_archiver,
+};
+use crate::api_wrappers::storage::{
+    ReplicationReport, get_storage_backend, get_storage_backend_names,
+    upload_to_all_storage_backends,
+};
+use crate::archivers::{Archiver, RequestArchiver};
+use crate::environment::environment;
+use crate::logger::logger;
+use crate::status::status_server;
+use crate::utils::clock::{SharedClock, system_clock};
+use crate::utils::hashing::get_content_hash;
+use anyhow::Result;
+use std::io::Cursor;
+
+pub enum RequestArchiverProgress {
+    // The data has just been requested.
+    Requested,
+    // The data has been requested before, but it's not ready yet.
+    Waiting,
+    Collected(Vec<u8>),
+}
+
+// Drives a `RequestArchiver` through its lifecycle: request -> wait -> collect. The pending request
+// is persisted in the database, so that the workflow can continue after the program restarts.
+pub struct RequestArchiverWorkflow<T: RequestArchiver> {
+    archiver: T,
+    database: DatabaseClient,
+    // The message containing the download link of the collected data, if it was sent via mail.
+    collected_message: Option<MailMessage>,
+    clock: SharedClock,
+}
+
+impl<T: RequestArchiver + Sync> RequestArchiverWorkflow<T> {
+    pub fn new(archiver: T) -> Self {
+        Self::from_database(archiver, DatabaseClient::new())
+    }
+
+    pub fn from_database(archiver: T, database: DatabaseClient) -> Self {
+        Self {
+            archiver,
+            database,
+            collected_message: None,
+            clock: system_clock(),
+        }
+    }
+
+    // Used for the dates of requests, and of stored backups.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.database = self.database.with_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
+
+    pub fn get_archiver(&self) -> &T {
+        &self.archiver
+    }
+
+    // Moves the workflow by a single step. Collected data isn't considered archived until
+    // `complete` is called, so that it's requested again only after it has been stored safely.
+    pub async fn advance(&mut self) -> Result<RequestArchiverProgress> {
+        let app_name = self.archiver.get_identificator();
+        let pending_request = self.database.get_pending_request(app_name.clone());
+
+        let Some(request) = pending_request else {
+            self.archiver.request_data().await?;
+            self.database
+                .add_pending_request(app_name, self.clock.now());
+            return Ok(RequestArchiverProgress::Requested);
+        };
+
+        let collected_data = match self.archiver.get_mail_rule() {
+            Some(rule) if environment().imap.enable => {
+                self.collect_data_from_mail(&rule, &request).await?
+            }
+            _ => self.archiver.collect_data(&request).await?,
+        };
+
+        match collected_data {
+            Some(data) => Ok(RequestArchiverProgress::Collected(data)),
+            None => Ok(RequestArchiverProgress::Waiting),
+        }
+    }
+
+    async fn collect_data_from_mail(
+        &mut self,
+        rule: &MailRule,
+        request: &PendingRequest,
+    ) -> Result<Option<Vec<u8>>> {
+        let handled_ids = self.database.get_handled_mail_ids();
+        let mut mail_client = ImapClient::new().await?;
+        let messages = mail_client
+            .fetch_matching_messages(rule, request.requested_at, &handled_ids)
+            .await?;
+        mail_client.logout().await;
+
+        let Some((message, url)) = pick_unhandled_download_url(messages, rule, &self.database)
+        else {
+            return Ok(None);
+        };
+
+        let data = self.archiver.download_data(&url).await?;
+        self.collected_message = Some(message);
+        Ok(Some(data))
+    }
+
+    pub fn complete(&mut self) {
+        let app_name = self.archiver.get_identificator();
+
+        if let Some(message) = self.collected_message.take() {
+            self.database
+                .add_handled_mail(app_name.clone(), &message.id);
+        }
+        self.database.remove_pending_request(app_name);
+    }
+}
+
+pub enum BackupOutcome {
+    // The data is identical to the previously stored backup, so it hasn't been uploaded again.
+    Unchanged,
+    Replicated(ReplicationReport),
+    // The data couldn't be prepared for uploading, so it hasn't been stored anywhere.
+    Failed,
+}
+
+impl BackupOutcome {
+    // Whether the data is safely kept in at least a single place.
+    pub fn is_stored(&self) -> bool {
+        match self {
+            Self::Unchanged => true,
+            Self::Replicated(report) => !report.succeeded.is_empty(),
+            Self::Failed => false,
+        }
+    }
+}
+
+// Backups aren't held by backends which have failed to store them, nor by ones added to the
+// configuration later.
+fn is_stored_in_all_backends(database: &DatabaseClient, path: &str) -> bool {
+    let locations = database.get_backup_locations(path);
+    get_storage_backend_names()
+        .iter()
+        .all(|name| locations.contains(name))
+}
+
+// Uploads archived data to all configured storage backends, logging the outcome of each one. If
+// any of them fails, the status server is notified, but the others are still uploaded to. Data
+// identical to the previously stored backup is skipped, as long as every configured backend holds
+// that backup. Either way, the decision is recorded in the database. `clock` should be the one of
+// the task, so that the backup can be found by its run.
+pub async fn store_backup<T: Archiver>(
+    archiver: &T,
+    data: Vec<u8>,
+    clock: &SharedClock,
+) -> BackupOutcome {
+    let app_name = archiver.get_identificator().as_str();
+    let database = DatabaseClient::new().with_clock(clock.clone());
+    let content_hash = get_content_hash(&data);
+    let size = data.len() as u64;
+
+    if environment().storage.skip_unchanged
+        && let Some(last_backup) = database.get_last_stored_backup(archiver.get_identificator())
+        && last_backup.content_hash == content_hash
+        && is_stored_in_all_backends(&database, &last_backup.path)
+    {
+        logger().log(&format!(
+            "No changes in {} export since `{}`, skipping the upload.",
+            app_name, last_backup.path
+        ));
+        database.add_backup_record(&BackupRecord {
+            app_name,
+            path: last_backup.path,
+            content_hash,
+            size,
+            status: BackupStatus::Unchanged,
+            created_at: clock.now(),
+        });
+        return BackupOutcome::Unchanged;
+    }
+
+    // The hash is computed beforehand, as encrypting the same data twice gives different results.
+    let payload = if environment().backup_encryption.enable {
+        match encrypt_backup(Cursor::new(data)).await {
+            Ok(payload) => payload,
+            Err(error) => {
+                report_archiving_error(error, format!("Failed to encrypt {} backup!", app_name));
+                return BackupOutcome::Failed;
+            }
+        }
+    } else {
+        BackupPayload::Raw(data)
+    };
+
+    let path = get_backup_path_for_archiver(archiver, clock.as_ref());
+    let report = upload_to_all_storage_backends(&path, get_backup_filename(), &payload).await;
+
+    for name in &report.succeeded {
+        logger().log(&format!("Stored `{}` in `{}`.", path, name));
+    }
+    for (name, error) in &report.failed {
+        logger().error(&format!(
+            "Failed to store `{}` in `{}`! Details: {:#}",
+            path, name, error
+        ));
+    }
+
+    if !report.failed.is_empty() {
+        let new_status = format!(
+            "Failed to store `{}` in: {}!",
+            path,
+            report.get_failed_names().join(", ")
+        );
+        status_server().set_error_message(Some(new_status));
+    } else if report.succeeded.is_empty() {
+        logger().error("No storage backends are configured! Backup hasn't been stored anywhere.");
+        status_server().set_error_message(Some("No storage backends are configured!".to_string()));
+    }
+
+    if !report.succeeded.is_empty() {
+        database.add_backup_record(&BackupRecord {
+            app_name,
+            path: path.clone(),
+            content_hash,
+            size,
+            status: BackupStatus::Stored,
+            created_at: clock.now(),
+        });
+    }
+    for name in &report.succeeded {
+        database.add_backup_location(&path, name);
+    }
+
+    // Old backups are pruned only where the new one has been stored successfully.
+    for name in &report.succeeded {
+        prune_old_backups(archiver, name, clock).await;
+    }
+
+    BackupOutcome::Replicated(report)
+}
+
+// Deletes backups which aren't kept by the archiver's retention policy from a single backend, and
+// records that in the database. Failures are only logged, as the new backup has already been
+// stored anyway.
+async fn prune_old_backups<T: Archiver>(archiver: &T, backend_name: &str, clock: &SharedClock) {
+    let policy = archiver.get_retention_policy();
+    let prefix = get_backup_prefix_for_archiver(archiver);
+    let is_dry_run = environment().storage.retention_dry_run;
+
+    let result = match get_storage_backend(backend_name).await {
+        Ok(backend) => prune_backups(backend.as_ref(), &prefix, &policy, is_dry_run).await,
+        Err(error) => Err(error),
+    };
+
+    match result {
+        Ok(pruned_backups) => {
+            let database = DatabaseClient::new().with_clock(clock.clone());
+            for backup in pruned_backups {
+                if is_dry_run {
+                    logger().log(&format!(
+                        "Would prune `{}` from `{}` (dry run).",
+                        backup.directory, backend_name
+                    ));
+                } else {
+                    database.mark_backup_pruned(&backup.directory, backend_name);
+                    logger().log(&format!(
+                        "Pruned `{}` from `{}`.",
+                        backup.directory, backend_name
+                    ));
+                }
+            }
+        }
+        Err(error) => {
+            logger().error(&format!(
+                "Failed to prune old backups from `{}`! Details: {:#}",
+                backend_name, error
+            ));
+        }
+    }
+}
+
+pub fn report_archiving_error(error: anyhow::Error, status_message: String) {
+    // The alternate format includes all of the error's causes.
+    let error_message = format!("{:#}", error);
+    logger().error(&error_message);
+    status_server().set_error_message(Some(status_message));
+}
@@ -0,0 +1,51 @@
+use crate::api_wrappers::database::DatabaseClient;
+use crate::api_wrappers::storage::verification::verify_backups;
+use crate::environment::environment;
+use crate::logger::logger;
+use crate::schedule::tasks::Task;
+use crate::schedule::tasks::utils::types::TaskConfig;
+use crate::status::status_server;
+use crate::utils::constants::ArchiverIdentificator;
+use crate::{init_new_task, task_callback};
+use anyhow::Result;
+
+// Problems with the backups themselves are reported right away, as retrying wouldn't fix them.
+async fn callback() -> Result<()> {
+    let records = DatabaseClient::new().get_stored_backups();
+
+    let mut amount_of_verified_backups = 0;
+    let mut amount_of_problems = 0;
+    for archiver in ArchiverIdentificator::get_all() {
+        let report = verify_backups(&archiver.as_str(), &records).await;
+        amount_of_verified_backups += report.results.len();
+        amount_of_problems += report.get_amount_of_problems();
+
+        for description in report.get_problems_descriptions() {
+            logger().error(&description);
+        }
+    }
+
+    if amount_of_problems > 0 {
+        status_server().set_error_message(Some(format!(
+            "Verification found {} problem(s) with stored backups!",
+            amount_of_problems
+        )));
+    } else {
+        logger().log(&format!(
+            "Verified {} stored backup(s), no problems found.",
+            amount_of_verified_backups
+        ));
+    }
+    Ok(())
+}
+
+init_new_task!(TaskConfig {
+    callback: task_callback!(callback),
+    schedule: environment().verification.schedule.clone(),
+    catch_up_policy: environment().verification.catch_up,
+    retry_policy: environment().task_retry.clone(),
+    timeout_seconds: environment().verification.timeout,
+    resource_locks: vec![],
+    app_name: ArchiverIdentificator::Verification,
+    is_enabled: environment().verification.enable,
+});
@@ -4,10 +4,15 @@ use crate::tui::tui;
 use crate::utils::constants::LOCAL_POLLING_RATE_MS;
 use crate::utils::exit::{disable_terminal_raw_mode, exit, leave_alternate_terminal_screen_mode};
 use crate::utils::startup::{enable_terminal_alternate_screen_mode, enable_terminal_raw_mode};
-use signal_hook::consts::{SIGCONT, SIGINT, SIGTERM, SIGTSTP};
+use once_cell::sync::Lazy;
+use signal_hook::consts::{SIGCONT, SIGHUP, SIGINT, SIGTERM, SIGTSTP};
 use signal_hook::iterator::Signals;
 use std::thread;
 use std::time::Duration;
+use tokio::sync::Notify;
+
+// Handled by the `run` option, which owns the Scheduler.
+static RELOAD_REQUESTS: Lazy<Notify> = Lazy::new(Notify::new);
 
 // Blocks until we have control of the terminal.
 fn wait_until_running_in_foreground() {
@@ -55,6 +60,16 @@ fn resume() {
     ui.rerender(None);
 }
 
+fn request_reload() {
+    logger().log("Reloading the configuration...");
+    RELOAD_REQUESTS.notify_one();
+}
+
+// Returns once `SIGHUP` has been received. Requests made before waiting aren't lost.
+pub async fn wait_for_reload_request() {
+    RELOAD_REQUESTS.notified().await;
+}
+
 pub struct SignalEvent {
     signal: i32,
     on_trigger: Box<dyn Fn()>,
@@ -78,6 +93,10 @@ fn get_handled_events() -> Vec<SignalEvent> {
             signal: SIGCONT,
             on_trigger: Box::new(resume),
         },
+        SignalEvent {
+            signal: SIGHUP,
+            on_trigger: Box::new(request_reload),
+        },
     ]
 }
 
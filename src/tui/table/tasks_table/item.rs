@@ -4,10 +4,17 @@ use crate::tui::table::TableItem;
 pub struct TasksTableItem {
     pub name: String,
     pub next_run: String,
+    pub status: String,
+    // Selected rows are marked in the ID column.
+    pub is_selected: bool,
 }
 
 impl TableItem for TasksTableItem {
     fn get_value_as_string_array(&self) -> Vec<String> {
-        vec![self.name.clone(), self.next_run.clone()]
+        vec![
+            self.name.clone(),
+            self.next_run.clone(),
+            self.status.clone(),
+        ]
     }
 }
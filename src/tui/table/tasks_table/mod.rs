@@ -1,3 +1,4 @@
 pub mod item;
+pub mod selection;
 pub mod table;
 pub mod utils;
@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// ID of the row selected in the tasks table (counting from 1), or 0 if none is selected.
+static SELECTED_ROW_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub fn get_selected_row_id() -> Option<usize> {
+    match SELECTED_ROW_ID.load(Ordering::SeqCst) {
+        0 => None,
+        id => Some(id),
+    }
+}
+
+// Both wrap around the table. If nothing is selected yet, the first or the last row gets selected.
+pub fn select_next_row(amount_of_rows: usize) {
+    let next_id = match get_selected_row_id() {
+        Some(id) if id < amount_of_rows => id + 1,
+        _ => 1,
+    };
+    SELECTED_ROW_ID.store(next_id.min(amount_of_rows), Ordering::SeqCst);
+}
+
+pub fn select_previous_row(amount_of_rows: usize) {
+    let previous_id = match get_selected_row_id() {
+        Some(id) if id > 1 => id - 1,
+        _ => amount_of_rows,
+    };
+    SELECTED_ROW_ID.store(previous_id, Ordering::SeqCst);
+}
@@ -35,6 +35,7 @@ impl Table<TasksTableItem> for TasksTable {
             get_centered_cell_from_string("ID"),
             get_centered_cell_from_string("Name"),
             get_centered_cell_from_string("Next run"),
+            get_centered_cell_from_string("Status"),
         ]);
         self.table.load_preset(UTF8_FULL);
         self.table.apply_modifier(UTF8_ROUND_CORNERS);
@@ -51,7 +52,11 @@ impl Table<TasksTableItem> for TasksTable {
 
         for (name, item) in &self.items {
             let mut row: Vec<String> = Vec::new();
-            row.push(name.to_string());
+            if item.is_selected {
+                row.push(format!("> {}", name));
+            } else {
+                row.push(name.to_string());
+            }
             row.append(&mut item.get_value_as_string_array());
             let stylized_row = row
                 .iter()
@@ -92,6 +97,8 @@ mod tests {
                 TasksTableItem {
                     name: "Task name".to_string(),
                     next_run: "Next run".to_string(),
+                    status: "Status".to_string(),
+                    is_selected: false,
                 },
             );
 
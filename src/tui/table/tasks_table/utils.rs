@@ -1,15 +1,30 @@
 use crate::tui::table::Table;
+use crate::tui::table::tasks_table::selection::get_selected_row_id;
 use crate::tui::table::tasks_table::{item::TasksTableItem, table::TasksTable};
 use crate::tui::table::utils::ThreadSafeTaskData;
+use crate::utils::time::format_system_time;
+
+fn format_next_run(task: &ThreadSafeTaskData) -> String {
+    let seconds = task.next_run.as_secs();
+    // Due tasks are about to run, so their exact date isn't interesting.
+    if seconds == 0 {
+        return "0s".to_string();
+    }
+    format!("{}s ({})", seconds, format_system_time(task.next_run_at))
+}
 
 pub fn add_tasks_to_tasks_table(tasks: Vec<ThreadSafeTaskData>, table: &mut TasksTable) {
+    let selected_row_id = get_selected_row_id();
     let mut id = 1;
+    #[allow(clippy::explicit_counter_loop)]
     for task in tasks {
         table.add_item(
             id.to_string(),
             TasksTableItem {
+                next_run: format_next_run(&task),
                 name: task.name,
-                next_run: format!("{}s", task.next_run.as_secs()),
+                status: task.status,
+                is_selected: selected_row_id == Some(id),
             },
         );
         id += 1;
@@ -7,12 +7,14 @@ use crate::tui::{self, TerminalUserInterface};
 use crate::utils::terminal::{println, refresh_table_in_tui};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 #[derive(Clone, Debug)]
 pub struct ThreadSafeTaskData {
     pub name: String,
     pub next_run: Duration,
+    pub next_run_at: SystemTime,
+    pub status: String,
 }
 
 impl ThreadSafeTaskData {
@@ -20,6 +22,8 @@ impl ThreadSafeTaskData {
         Self {
             name: task.get_app_name().as_str(),
             next_run: task.get_time_until_next_run(),
+            next_run_at: task.get_next_run(),
+            status: task.describe_status(),
         }
     }
 }
@@ -0,0 +1,102 @@
+use super::types::AsyncOutput;
+use once_cell::sync::Lazy;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Notify;
+
+// Source of the current time, and of waiting for some of it to pass. Everything related to
+// scheduling goes through it, so that tests can replace it with a `FakeClock`.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+    fn sleep(&self, duration: Duration) -> AsyncOutput;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> AsyncOutput {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+static SYSTEM_CLOCK: Lazy<SharedClock> = Lazy::new(|| Arc::new(SystemClock));
+
+pub fn system_clock() -> SharedClock {
+    SYSTEM_CLOCK.clone()
+}
+
+// Time which moves only when `advance` is called, waking up everything sleeping until then. Lets
+// tests go through weeks of schedule instantly. Clones share the same time.
+#[derive(Clone)]
+pub struct FakeClock {
+    now: Arc<Mutex<SystemTime>>,
+    advanced: Arc<Notify>,
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        // 2025-01-01T00:00:00Z, so that dates in tests are easy to reason about.
+        Self::new(UNIX_EPOCH + Duration::from_secs(1_735_689_600))
+    }
+}
+
+impl FakeClock {
+    pub fn new(now: SystemTime) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+            advanced: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().expect("Failed to access the fake clock!") += duration;
+        self.advanced.notify_waiters();
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().expect("Failed to access the fake clock!")
+    }
+
+    fn sleep(&self, duration: Duration) -> AsyncOutput {
+        let clock = self.clone();
+        let wake_up_at = clock.now() + duration;
+        Box::pin(async move {
+            loop {
+                // Registered before checking the time, so that an advance in between isn't missed.
+                let advanced = clock.advanced.notified();
+                if clock.now() >= wake_up_at {
+                    return;
+                }
+                advanced.await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod fake_clock {
+        use super::super::*;
+
+        #[tokio::test]
+        async fn wakes_up_sleepers_once_advanced_enough() {
+            let clock = FakeClock::default();
+            let sleeper = tokio::spawn(clock.sleep(Duration::from_secs(60)));
+
+            clock.advance(Duration::from_secs(30));
+            tokio::task::yield_now().await;
+            assert!(!sleeper.is_finished());
+
+            clock.advance(Duration::from_secs(30));
+            sleeper.await.unwrap();
+        }
+    }
+}
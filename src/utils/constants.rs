@@ -1,3 +1,4 @@
+use crate::environment::environment;
 use crate::utils::uuid;
 
 fn tests_identificator_as_str() -> String {
@@ -7,7 +8,10 @@ fn tests_identificator_as_str() -> String {
 #[derive(Clone)]
 pub enum ArchiverIdentificator {
     Tests,
-    Letterboxd,
+    // Named accounts are stored under `letterboxd/<account>/`, the unnamed one under `letterboxd/`.
+    Letterboxd(Option<String>),
+    // Not an actual archiver, identifies the task periodically verifying stored backups.
+    Verification,
 }
 
 #[derive(Clone)]
@@ -16,13 +20,28 @@ pub enum APIWrapperIdentificator {
     Letterboxd,
     S3,
     Database,
+    Mail,
+    LocalStorage,
 }
 
 impl ArchiverIdentificator {
+    // Every archiver supported by the program (one per configured account), without the ones used
+    // only in tests.
+    pub fn get_all() -> Vec<Self> {
+        environment()
+            .letterboxd
+            .accounts
+            .iter()
+            .map(|account| ArchiverIdentificator::Letterboxd(account.name.clone()))
+            .collect()
+    }
+
     pub fn as_str(&self) -> String {
         match self {
             ArchiverIdentificator::Tests => tests_identificator_as_str(),
-            ArchiverIdentificator::Letterboxd => "letterboxd".to_string(),
+            ArchiverIdentificator::Letterboxd(None) => "letterboxd".to_string(),
+            ArchiverIdentificator::Letterboxd(Some(account)) => format!("letterboxd/{}", account),
+            ArchiverIdentificator::Verification => "verification".to_string(),
         }
     }
 }
@@ -34,8 +53,13 @@ impl APIWrapperIdentificator {
             APIWrapperIdentificator::Letterboxd => "letterboxd".to_string(),
             APIWrapperIdentificator::S3 => "s3".to_string(),
             APIWrapperIdentificator::Database => "database".to_string(),
+            APIWrapperIdentificator::Mail => "mail".to_string(),
+            APIWrapperIdentificator::LocalStorage => "local_storage".to_string(),
         }
     }
 }
 
 pub static LOCAL_POLLING_RATE_MS: u64 = 250;
+
+// Held by tasks using the browser, as WebDriver supports only a single session at a time.
+pub static WEBDRIVER_RESOURCE_LOCK: &str = "webdriver";
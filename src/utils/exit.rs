@@ -23,3 +23,11 @@ pub fn exit() {
     disable_terminal_raw_mode();
     return_zero();
 }
+
+// Same as `exit`, but lets the caller (e.g. a script running one of the options) know that
+// something went wrong.
+pub fn exit_with_failure() {
+    leave_alternate_terminal_screen_mode();
+    disable_terminal_raw_mode();
+    process::exit(1);
+}
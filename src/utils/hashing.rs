@@ -0,0 +1,91 @@
+use sha2::{Digest, Sha256};
+use std::io::{self, Cursor, Read, Seek};
+use zip::ZipArchive;
+
+// Hashes only names and contents of the entries, so that two archives containing the same files
+// are considered identical, even if they have been packed at different times. Returns `None` if
+// the data isn't a valid zip archive.
+fn get_normalized_zip_hash(data: impl Read + Seek) -> Option<String> {
+    let mut archive = ZipArchive::new(data).ok()?;
+
+    let mut names: Vec<String> = archive.file_names().map(String::from).collect();
+    names.sort();
+
+    let mut hasher = Sha256::new();
+    for name in names {
+        let mut entry = archive.by_name(&name).ok()?;
+
+        // Lengths are included, so that the boundaries between entries can't be shifted. Reading
+        // an entry fails if its length doesn't match the declared one.
+        hasher.update((name.len() as u64).to_le_bytes());
+        hasher.update(name.as_bytes());
+        hasher.update(entry.size().to_le_bytes());
+        io::copy(&mut entry, &mut hasher).ok()?;
+    }
+
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+// Hash used to tell whether an export has changed since the previous one. Zip archives are
+// normalized first, every other payload is hashed as is.
+pub fn get_content_hash(data: &[u8]) -> String {
+    read_content_hash(Cursor::new(data)).expect("Failed to read data held in memory!")
+}
+
+// Same as `get_content_hash`, but the data is read as it's hashed, e.g. from a file.
+pub fn read_content_hash(mut data: impl Read + Seek) -> io::Result<String> {
+    if let Some(hash) = get_normalized_zip_hash(&mut data) {
+        return Ok(hash);
+    }
+
+    data.rewind()?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut data, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    mod get_content_hash {
+        use super::super::*;
+        use std::io::Write;
+        use zip::DateTime;
+        use zip::write::{SimpleFileOptions, ZipWriter};
+
+        fn create_zip(files: &[(&str, &str)], year: u16) -> Vec<u8> {
+            let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+            let options = SimpleFileOptions::default()
+                .last_modified_time(DateTime::from_date_and_time(year, 1, 1, 0, 0, 0).unwrap());
+            for (name, contents) in files {
+                writer.start_file(*name, options).unwrap();
+                writer.write_all(contents.as_bytes()).unwrap();
+            }
+            writer.finish().unwrap().into_inner()
+        }
+
+        #[test]
+        fn hashes_raw_data() {
+            assert_eq!(
+                get_content_hash(b"data"),
+                "3a6eb0790f39ac87c94f3856b2dd2c5d110e6811602261a9a923d3bb23adc8b7"
+            );
+        }
+
+        #[test]
+        fn ignores_zip_entries_timestamps_and_order() {
+            let zip = create_zip(&[("a.csv", "1"), ("b.csv", "2")], 2020);
+            let newer_zip = create_zip(&[("b.csv", "2"), ("a.csv", "1")], 2025);
+
+            assert_ne!(zip, newer_zip);
+            assert_eq!(get_content_hash(&zip), get_content_hash(&newer_zip));
+        }
+
+        #[test]
+        fn detects_changed_zip_entries() {
+            let zip = create_zip(&[("a.csv", "1")], 2020);
+            let changed_zip = create_zip(&[("a.csv", "2")], 2020);
+
+            assert_ne!(get_content_hash(&zip), get_content_hash(&changed_zip));
+        }
+    }
+}
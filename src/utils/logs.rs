@@ -1,14 +1,14 @@
-use crate::environment;
+use crate::environment::utils::generic;
+use crate::utils::clock::SystemClock;
 use crate::utils::time::get_current_path_friendly_formatted_date;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::{fs, io, os};
 
-// For example: "~/.local/state/asmda/logs".
+// For example: "~/.local/state/asmda/logs". Doesn't depend on the rest of the environment, so that
+// the description of the `logs` option can be built even if it's invalid.
 fn get_logs_directory_path() -> PathBuf {
-    let config = environment::environment();
-    let raw_path = config.metadata.logs_directory_path.clone();
-    PathBuf::from(&raw_path)
+    PathBuf::from(generic::get_logs_directory_path())
 }
 
 // For example: "~/.local/state/asmda", used for `latest.log` symlink in the root folder of program's
@@ -49,7 +49,7 @@ fn create_symlink(original_path: &Path, link_path: &Path) -> io::Result<()> {
 pub fn validate_log_directory_setup() {
     let logs_directory_path = get_logs_directory_path();
 
-    let current_formatted_date = get_current_path_friendly_formatted_date();
+    let current_formatted_date = get_current_path_friendly_formatted_date(&SystemClock);
     let new_log_filename = format!("{}.log", current_formatted_date);
     let new_log_file_path = format!(
         "{}/{}",
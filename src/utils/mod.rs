@@ -1,6 +1,8 @@
+pub mod clock;
 pub mod constants;
 pub mod encryption;
 pub mod exit;
+pub mod hashing;
 pub mod logs;
 pub mod multithreading;
 pub mod startup;
@@ -123,7 +123,7 @@ mod tests {
             logger().reinitialize();
             tui().reinitialize();
             setup_tui(Some(Arc::new(Box::new(|| {
-                let tasks = vec![tasks::letterboxd::get_task()];
+                let tasks = tasks::letterboxd::get_tasks();
                 convert_tasks_to_thread_safe_task_data(tasks)
             }))));
             logger().log_without_date("Starting up...");
@@ -133,11 +133,11 @@ mod tests {
                 output,
                 [
                     "Starting up...",
-                    "╭────┬────────────┬──────────╮",
-                    "│ ID │    Name    │ Next run │",
-                    "╞════╪════════════╪══════════╡",
-                    "│  1 │ letterboxd │    0s    │",
-                    "╰────┴────────────┴──────────╯",
+                    "╭────┬────────────┬──────────┬───────────╮",
+                    "│ ID │    Name    │ Next run │   Status  │",
+                    "╞════╪════════════╪══════════╪═══════════╡",
+                    "│  1 │ letterboxd │    0s    │ Scheduled │",
+                    "╰────┴────────────┴──────────┴───────────╯",
                 ]
             );
         }
@@ -1,16 +1,30 @@
-use chrono::{DateTime, Local};
+use super::clock::Clock;
+use chrono::{DateTime, Local, NaiveDateTime};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-pub fn get_current_formatted_date() -> String {
-    let now = SystemTime::now();
-    let datetime: DateTime<Local> = now.into();
+pub fn format_system_time(system_time: SystemTime) -> String {
+    let datetime: DateTime<Local> = system_time.into();
     datetime.format("%Y/%m/%d - %H:%M:%S").to_string()
 }
 
-pub fn get_current_path_friendly_formatted_date() -> String {
-    let now = SystemTime::now();
-    let datetime: DateTime<Local> = now.into();
-    datetime.format("%Y-%m-%dT%H:%M:%S").to_string()
+pub fn get_current_formatted_date(clock: &dyn Clock) -> String {
+    format_system_time(clock.now())
+}
+
+static PATH_FRIENDLY_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+pub fn get_current_path_friendly_formatted_date(clock: &dyn Clock) -> String {
+    let datetime: DateTime<Local> = clock.now().into();
+    datetime.format(PATH_FRIENDLY_DATE_FORMAT).to_string()
+}
+
+pub fn format_path_friendly_date(datetime: &NaiveDateTime) -> String {
+    datetime.format(PATH_FRIENDLY_DATE_FORMAT).to_string()
+}
+
+// Reverses `get_current_path_friendly_formatted_date`. Returns `None` if the string is invalid.
+pub fn parse_path_friendly_formatted_date(formatted_string: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(formatted_string, PATH_FRIENDLY_DATE_FORMAT).ok()
 }
 
 pub fn system_time_to_str(system_time: SystemTime) -> String {
@@ -29,6 +43,16 @@ pub fn str_to_system_time(formatted_string: String) -> SystemTime {
     UNIX_EPOCH + Duration::from_secs(timestamp)
 }
 
+// Drops the fraction of a second, which isn't kept by `system_time_to_str`, so that a timestamp can
+// be compared with the ones read back from the database.
+pub fn truncate_to_seconds(system_time: SystemTime) -> SystemTime {
+    let timestamp = system_time
+        .duration_since(UNIX_EPOCH)
+        .expect("Error! Passed date before the Unix epoch.")
+        .as_secs();
+    UNIX_EPOCH + Duration::from_secs(timestamp)
+}
+
 #[cfg(test)]
 mod tests {
     mod system_time_to_str {
@@ -61,4 +85,18 @@ mod tests {
             assert_eq!(timestamp, str_to_system_time(timestamp_str));
         }
     }
+
+    mod truncate_to_seconds {
+        use super::super::*;
+
+        #[test]
+        fn drops_fraction_of_second() {
+            let timestamp = UNIX_EPOCH + Duration::from_millis(5999);
+
+            assert_eq!(
+                truncate_to_seconds(timestamp),
+                UNIX_EPOCH + Duration::from_secs(5)
+            );
+        }
+    }
 }
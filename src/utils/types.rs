@@ -1,6 +1,9 @@
+use super::clock::SharedClock;
 use std::future::Future;
 use std::pin::Pin;
 
 pub type AsyncOutput = Pin<Box<dyn Future<Output = ()> + Send>>;
-pub type AsyncFnMut = Box<dyn FnMut() -> AsyncOutput + Send>;
+pub type AsyncResultOutput = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>;
+// Used by task callbacks, which get the clock of their task, see `Task::with_clock`.
+pub type AsyncFnMut = Box<dyn FnMut(SharedClock) -> AsyncResultOutput + Send>;
 pub type AsyncFn = Box<dyn Fn() -> AsyncOutput + Send>;
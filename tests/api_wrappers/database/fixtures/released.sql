@@ -0,0 +1,4 @@
+-- Layout of the last released version, which kept only the schedule.
+CREATE TABLE IF NOT EXISTS schedule (id INTEGER PRIMARY KEY, app_name TEXT NOT NULL, next_run TEXT NOT NULL);
+
+INSERT INTO schedule (app_name, next_run) VALUES ('letterboxd', '1735689600');
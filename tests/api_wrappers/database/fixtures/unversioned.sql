@@ -0,0 +1,44 @@
+-- Unreleased layout, with the tables added since the last release, but without schema versioning.
+CREATE TABLE IF NOT EXISTS schedule (
+    id          INTEGER PRIMARY KEY,
+    app_name    TEXT NOT NULL,
+    next_run    TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS pending_requests (
+    id              INTEGER PRIMARY KEY,
+    app_name        TEXT NOT NULL,
+    requested_at    TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS handled_mails (
+    id          INTEGER PRIMARY KEY,
+    app_name    TEXT NOT NULL,
+    message_id  TEXT NOT NULL,
+    handled_at  TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS backups (
+    id              INTEGER PRIMARY KEY,
+    app_name        TEXT NOT NULL,
+    path            TEXT NOT NULL,
+    content_hash    TEXT NOT NULL,
+    size            INTEGER NOT NULL,
+    status          TEXT NOT NULL,
+    created_at      TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS runs (
+    id              INTEGER PRIMARY KEY,
+    app_name        TEXT NOT NULL,
+    started_at      TEXT NOT NULL,
+    finished_at     TEXT,
+    outcome         TEXT NOT NULL,
+    error_message   TEXT,
+    bytes_uploaded  INTEGER,
+    storage_path    TEXT
+);
+
+INSERT INTO schedule (app_name, next_run) VALUES ('letterboxd', '1735689600');
+INSERT INTO handled_mails (app_name, message_id, handled_at)
+    VALUES ('letterboxd', '<fixture@asmda>', '1735689600');
+INSERT INTO backups (app_name, path, content_hash, size, status, created_at)
+    VALUES ('letterboxd', 'letterboxd/2025-01-01T00:00:00', 'fixture_hash', 1024, 'stored', '1735689600');
+INSERT INTO runs (app_name, started_at, finished_at, outcome, bytes_uploaded, storage_path)
+    VALUES ('letterboxd', '1735689600', '1735689660', 'succeeded', 1024, 'letterboxd/2025-01-01T00:00:00');
@@ -0,0 +1,101 @@
+use asmda::api_wrappers::database::DatabaseClient;
+use asmda::api_wrappers::database::migrations::{
+    get_latest_schema_version, get_schema_version, run_migrations,
+};
+use asmda::utils::constants::ArchiverIdentificator;
+use rusqlite::Connection;
+use std::time::{Duration, UNIX_EPOCH};
+
+static UNVERSIONED_FIXTURE: &str = include_str!("fixtures/unversioned.sql");
+static RELEASED_FIXTURE: &str = include_str!("fixtures/released.sql");
+
+fn get_connection_from_fixture(fixture: &str) -> Connection {
+    let connection = Connection::open_in_memory().unwrap();
+    connection.execute_batch(fixture).unwrap();
+    connection
+}
+
+fn get_unversioned_connection() -> Connection {
+    get_connection_from_fixture(UNVERSIONED_FIXTURE)
+}
+
+#[cfg(test)]
+mod tests {
+    mod migrations {
+        use super::super::*;
+
+        #[test]
+        fn upgrades_unversioned_database() {
+            let mut connection = get_unversioned_connection();
+            assert_eq!(get_schema_version(&connection).unwrap(), 0);
+
+            run_migrations(&mut connection).unwrap();
+            assert_eq!(
+                get_schema_version(&connection).unwrap(),
+                get_latest_schema_version()
+            );
+
+            // Data stored before the upgrade is still there.
+            let database = DatabaseClient::from_connection(connection);
+            assert_eq!(
+                database.get_next_run_by_app_name(ArchiverIdentificator::Letterboxd(None)),
+                Some(UNIX_EPOCH + Duration::from_secs(1735689600))
+            );
+            assert!(database.is_mail_handled("<fixture@asmda>"));
+            assert_eq!(database.get_stored_backups().len(), 1);
+            assert_eq!(database.get_runs(Some("letterboxd"), 10).len(), 1);
+        }
+
+        #[test]
+        fn upgrades_released_database() {
+            let mut connection = get_connection_from_fixture(RELEASED_FIXTURE);
+            assert_eq!(get_schema_version(&connection).unwrap(), 0);
+
+            run_migrations(&mut connection).unwrap();
+            assert_eq!(
+                get_schema_version(&connection).unwrap(),
+                get_latest_schema_version()
+            );
+
+            // The schedule is kept, and the tables added since then are usable.
+            let database = DatabaseClient::from_connection(connection);
+            assert_eq!(
+                database.get_next_run_by_app_name(ArchiverIdentificator::Letterboxd(None)),
+                Some(UNIX_EPOCH + Duration::from_secs(1735689600))
+            );
+            assert!(!database.is_mail_handled("<fixture@asmda>"));
+            assert!(database.get_stored_backups().is_empty());
+            assert!(database.get_runs(None, 10).is_empty());
+            assert!(database.take_run_request("letterboxd").is_none());
+            assert!(!database.is_task_paused("letterboxd"));
+        }
+
+        #[test]
+        fn can_run_repeatedly() {
+            let mut connection = Connection::open_in_memory().unwrap();
+
+            run_migrations(&mut connection).unwrap();
+            run_migrations(&mut connection).unwrap();
+
+            let amount_of_versions: u64 = connection
+                .query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(amount_of_versions, get_latest_schema_version());
+        }
+
+        #[test]
+        fn refuses_newer_schema() {
+            let mut connection = get_unversioned_connection();
+            run_migrations(&mut connection).unwrap();
+            connection
+                .execute(
+                    "INSERT INTO schema_version (version, description, applied_at)
+                        VALUES (?1, 'From the future', '0')",
+                    [get_latest_schema_version() + 1],
+                )
+                .unwrap();
+
+            assert!(run_migrations(&mut connection).is_err());
+        }
+    }
+}
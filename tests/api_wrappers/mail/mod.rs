@@ -0,0 +1,177 @@
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+static TEST_MESSAGE: &str = "From: Platform <no-reply@platform.com>\r
+Subject: Your data is ready\r
+Message-ID: <export@platform.com>\r
+\r
+Download your data here: https://platform.com/download/123\r
+";
+
+// A stand-in for an IMAP server, which always has a single matching message with UID 7. Returns
+// the port it listens on, and all commands it has received.
+async fn start_test_imap_server() -> (u16, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let received_commands = Arc::new(Mutex::new(Vec::new()));
+
+    let received_commands_clone = received_commands.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            writer.write_all(b"* OK IMAP ready\r\n").await.unwrap();
+
+            while let Ok(Some(line)) = lines.next_line().await {
+                received_commands_clone.lock().unwrap().push(line.clone());
+                let (tag, command) = line.split_once(' ').unwrap();
+
+                let response = if command.starts_with("UID SEARCH") {
+                    format!("* SEARCH 7\r\n{} OK SEARCH completed\r\n", tag)
+                } else if command.contains("HEADER.FIELDS (MESSAGE-ID)") {
+                    let headers = "Message-ID: <export@platform.com>\r\n\r\n";
+                    format!(
+                        "* 1 FETCH (UID 7 BODY[HEADER.FIELDS (MESSAGE-ID)] {{{}}}\r\n{})\r\n\
+                         {} OK FETCH completed\r\n",
+                        headers.len(),
+                        headers,
+                        tag
+                    )
+                } else if command.starts_with("UID FETCH") {
+                    format!(
+                        "* 1 FETCH (UID 7 BODY[] {{{}}}\r\n{})\r\n{} OK FETCH completed\r\n",
+                        TEST_MESSAGE.len(),
+                        TEST_MESSAGE,
+                        tag
+                    )
+                } else if command.starts_with("LOGOUT") {
+                    format!("* BYE\r\n{} OK LOGOUT completed\r\n", tag)
+                } else {
+                    format!("{} OK completed\r\n", tag)
+                };
+                writer.write_all(response.as_bytes()).await.unwrap();
+            }
+        }
+    });
+
+    (port, received_commands)
+}
+
+#[cfg(test)]
+mod tests {
+    mod mail {
+        use super::super::*;
+        use asmda::api_wrappers::database::DatabaseClient;
+        use asmda::api_wrappers::mail::types::MailRule;
+        use asmda::api_wrappers::mail::{ImapClient, pick_unhandled_download_url};
+        use asmda::environment::types::ImapEnvironment;
+        use asmda::utils::constants::ArchiverIdentificator;
+        use rusqlite::Connection;
+        use std::collections::HashSet;
+        use std::time::SystemTime;
+
+        fn get_test_config(port: u16) -> ImapEnvironment {
+            ImapEnvironment {
+                enable: true,
+                host: "127.0.0.1".to_string(),
+                port: port as u64,
+                tls: false,
+                username: Some("username".to_string()),
+                password: Some("password".to_string()),
+                mailbox: "INBOX".to_string(),
+            }
+        }
+
+        fn get_test_rule() -> MailRule {
+            MailRule {
+                sender: "no-reply@platform.com".to_string(),
+                subject: "Your data is ready".to_string(),
+                url_prefix: "https://platform.com/download".to_string(),
+            }
+        }
+
+        #[tokio::test]
+        async fn fetches_matching_messages() {
+            let (port, received_commands) = start_test_imap_server().await;
+
+            let mut client = ImapClient::from_config(&get_test_config(port))
+                .await
+                .unwrap();
+            let messages = client
+                .fetch_matching_messages(&get_test_rule(), SystemTime::now(), &HashSet::new())
+                .await
+                .unwrap();
+            client.logout().await;
+
+            assert_eq!(messages.len(), 1);
+            assert_eq!(messages[0].uid, 7);
+            assert_eq!(messages[0].id, "<export@platform.com>");
+
+            let commands = received_commands.lock().unwrap().clone();
+            assert_eq!(commands[0], "A1 LOGIN \"username\" \"password\"");
+            assert_eq!(commands[1], "A2 SELECT \"INBOX\"");
+            assert!(
+                commands[2]
+                    .contains("FROM \"no-reply@platform.com\" SUBJECT \"Your data is ready\"")
+            );
+            assert_eq!(
+                commands[3],
+                "A4 UID FETCH 7 (BODY.PEEK[HEADER.FIELDS (MESSAGE-ID)])"
+            );
+            assert_eq!(commands[4], "A5 UID FETCH 7 BODY.PEEK[]");
+        }
+
+        #[tokio::test]
+        async fn doesnt_fetch_handled_messages() {
+            let (port, received_commands) = start_test_imap_server().await;
+            let database = DatabaseClient::from_connection(Connection::open_in_memory().unwrap());
+            database.add_handled_mail(ArchiverIdentificator::Tests, "<export@platform.com>");
+
+            let mut client = ImapClient::from_config(&get_test_config(port))
+                .await
+                .unwrap();
+            let messages = client
+                .fetch_matching_messages(
+                    &get_test_rule(),
+                    SystemTime::now(),
+                    &database.get_handled_mail_ids(),
+                )
+                .await
+                .unwrap();
+            client.logout().await;
+
+            assert!(messages.is_empty());
+            let commands = received_commands.lock().unwrap().clone();
+            assert!(
+                !commands
+                    .iter()
+                    .any(|command| command.ends_with("BODY.PEEK[]"))
+            );
+        }
+
+        #[tokio::test]
+        async fn doesnt_pick_handled_messages_twice() {
+            let (port, _) = start_test_imap_server().await;
+            let database = DatabaseClient::from_connection(Connection::open_in_memory().unwrap());
+            let rule = get_test_rule();
+
+            let mut client = ImapClient::from_config(&get_test_config(port))
+                .await
+                .unwrap();
+            let messages = client
+                .fetch_matching_messages(&rule, SystemTime::now(), &HashSet::new())
+                .await
+                .unwrap();
+
+            let (message, url) =
+                pick_unhandled_download_url(messages.clone(), &rule, &database).unwrap();
+            assert_eq!(url, "https://platform.com/download/123");
+
+            database.add_handled_mail(ArchiverIdentificator::Tests, &message.id);
+            let picked = pick_unhandled_download_url(messages, &rule, &database);
+            assert!(picked.is_none());
+        }
+    }
+}
@@ -1 +1,3 @@
 mod browser;
+mod database;
+mod mail;
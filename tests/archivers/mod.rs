@@ -0,0 +1,100 @@
+use anyhow::Result;
+use asmda::api_wrappers::database::types::PendingRequest;
+use asmda::archivers::{Archiver, RequestArchiver};
+use asmda::utils::constants::ArchiverIdentificator;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+struct TestRequestArchiver {
+    requests_count: AtomicU32,
+    is_data_ready: AtomicBool,
+}
+
+impl TestRequestArchiver {
+    fn new() -> Self {
+        Self {
+            requests_count: AtomicU32::new(0),
+            is_data_ready: AtomicBool::new(false),
+        }
+    }
+}
+
+impl Archiver for TestRequestArchiver {
+    // `ArchiverIdentificator::Tests` changes its value on each call, which would make the pending
+    // request impossible to find. These tests use an in-memory database, so this is fine.
+    fn get_identificator(&self) -> ArchiverIdentificator {
+        ArchiverIdentificator::Letterboxd(None)
+    }
+}
+
+impl RequestArchiver for TestRequestArchiver {
+    async fn request_data(&self) -> Result<()> {
+        self.requests_count.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn collect_data(&self, _: &PendingRequest) -> Result<Option<Vec<u8>>> {
+        if self.is_data_ready.load(Ordering::SeqCst) {
+            Ok(Some(b"data".to_vec()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod request_archiver_workflow {
+        use super::super::*;
+        use asmda::api_wrappers::database::DatabaseClient;
+        use asmda::schedule::tasks::utils::workflows::{
+            RequestArchiverProgress, RequestArchiverWorkflow,
+        };
+        use rusqlite::Connection;
+
+        fn get_workflow() -> RequestArchiverWorkflow<TestRequestArchiver> {
+            let connection = Connection::open_in_memory().unwrap();
+            let database = DatabaseClient::from_connection(connection);
+            RequestArchiverWorkflow::from_database(TestRequestArchiver::new(), database)
+        }
+
+        #[tokio::test]
+        async fn requests_data_only_once() {
+            let mut workflow = get_workflow();
+
+            let first_progress = workflow.advance().await.unwrap();
+            let second_progress = workflow.advance().await.unwrap();
+
+            assert!(matches!(first_progress, RequestArchiverProgress::Requested));
+            assert!(matches!(second_progress, RequestArchiverProgress::Waiting));
+            let requests_count = workflow
+                .get_archiver()
+                .requests_count
+                .load(Ordering::SeqCst);
+            assert_eq!(requests_count, 1);
+        }
+
+        #[tokio::test]
+        async fn collects_data_and_requests_again_after_completion() {
+            let mut workflow = get_workflow();
+            workflow.advance().await.unwrap();
+
+            workflow
+                .get_archiver()
+                .is_data_ready
+                .store(true, Ordering::SeqCst);
+            let progress = workflow.advance().await.unwrap();
+            let RequestArchiverProgress::Collected(data) = progress else {
+                panic!("Data should've been collected!");
+            };
+            assert_eq!(data, b"data".to_vec());
+
+            // Until the data is stored, we should keep trying to collect it.
+            let progress = workflow.advance().await.unwrap();
+            assert!(matches!(progress, RequestArchiverProgress::Collected(_)));
+
+            workflow.complete();
+            let progress = workflow.advance().await.unwrap();
+            assert!(matches!(progress, RequestArchiverProgress::Requested));
+        }
+    }
+}
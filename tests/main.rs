@@ -1,2 +1,3 @@
 mod api_wrappers;
+mod archivers;
 mod schedule;
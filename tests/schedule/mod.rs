@@ -1,88 +1,324 @@
+use anyhow::{Result, bail};
+use asmda::environment::types::RetryPolicy;
 use asmda::logger::logger;
 use asmda::schedule;
 use asmda::schedule::tasks::Task;
+use asmda::schedule::tasks::utils::schedule::{CatchUpPolicy, TaskSchedule};
 use asmda::schedule::tasks::utils::types::TaskConfig;
+use asmda::task_callback;
+use asmda::utils::clock::{Clock, FakeClock, SharedClock};
 use asmda::utils::constants;
-use asmda::{init_new_task, task_callback};
+use asmda::utils::types::AsyncFnMut;
 use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime};
-use tokio::sync::Mutex as TokioMutex;
-use tokio::time::sleep;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 static FIRST_COUNTER: Lazy<Arc<Mutex<u32>>> = Lazy::new(|| Arc::new(Mutex::new(0)));
 static SECOND_COUNTER: Lazy<Arc<Mutex<u32>>> = Lazy::new(|| Arc::new(Mutex::new(0)));
-static THIRD_TIMER: Lazy<Arc<Mutex<SystemTime>>> = Lazy::new(|| Arc::new(SystemTime::now().into()));
+static FLAKY_COUNTER: Lazy<Arc<Mutex<u32>>> = Lazy::new(|| Arc::new(Mutex::new(0)));
+static FAILING_COUNTER: Lazy<Arc<Mutex<u32>>> = Lazy::new(|| Arc::new(Mutex::new(0)));
+static HANGING_COUNTER: Lazy<Arc<Mutex<u32>>> = Lazy::new(|| Arc::new(Mutex::new(0)));
+// Amounts of callbacks running at the moment, and the highest one seen, for each test.
+static LOCKED_RUNNING: AtomicU32 = AtomicU32::new(0);
+static LOCKED_MAX_RUNNING: AtomicU32 = AtomicU32::new(0);
+static LIMITED_RUNNING: AtomicU32 = AtomicU32::new(0);
+static LIMITED_MAX_RUNNING: AtomicU32 = AtomicU32::new(0);
+static THIRD_TIMER: Lazy<Arc<Mutex<SystemTime>>> = Lazy::new(|| Arc::new(UNIX_EPOCH.into()));
 
-async fn first_task_callback() {
+async fn first_task_callback() -> Result<()> {
     logger().debug("First task running!");
 
     let mut lock = FIRST_COUNTER.lock().unwrap();
     *lock += 1;
+    Ok(())
 }
 
-async fn second_task_callback() {
+async fn second_task_callback() -> Result<()> {
     logger().debug("Second task running!");
 
     let mut lock = SECOND_COUNTER.lock().unwrap();
     *lock += 1;
+    Ok(())
 }
 
-async fn third_task_callback() {
+async fn third_task_callback(clock: SharedClock) -> Result<()> {
     logger().debug("Third task running!");
 
     let mut lock = THIRD_TIMER.lock().unwrap();
-    *lock = SystemTime::now();
+    *lock = clock.now();
+    Ok(())
 }
 
-init_new_task!(
-    TaskConfig {
-        callback: task_callback!(first_task_callback),
-        run_interval_seconds: 3,
-        app_name: constants::ArchiverIdentificator::Tests,
-        is_enabled: true,
-    },
-    get_first_task
-);
+// Fails twice, and succeeds on the third attempt.
+async fn flaky_task_callback() -> Result<()> {
+    let mut lock = FLAKY_COUNTER.lock().unwrap();
+    *lock += 1;
+    if *lock < 3 {
+        bail!("Flaky task failed!");
+    }
+    Ok(())
+}
 
-init_new_task!(
-    TaskConfig {
-        callback: task_callback!(second_task_callback),
-        run_interval_seconds: 4,
-        app_name: constants::ArchiverIdentificator::Tests,
-        is_enabled: true,
-    },
-    get_second_task
-);
+async fn failing_task_callback() -> Result<()> {
+    let mut lock = FAILING_COUNTER.lock().unwrap();
+    *lock += 1;
+    bail!("Failing task failed!");
+}
+
+// Never finishes on its own.
+async fn hanging_task_callback(clock: SharedClock) -> Result<()> {
+    clock.sleep(Duration::from_secs(60)).await;
+    let mut lock = HANGING_COUNTER.lock().unwrap();
+    *lock += 1;
+    Ok(())
+}
+
+async fn track_concurrency(running: &AtomicU32, max_running: &AtomicU32, clock: SharedClock) {
+    let amount_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+    max_running.fetch_max(amount_running, Ordering::SeqCst);
+    clock.sleep(Duration::from_secs(2)).await;
+    running.fetch_sub(1, Ordering::SeqCst);
+}
+
+async fn locked_task_callback(clock: SharedClock) -> Result<()> {
+    track_concurrency(&LOCKED_RUNNING, &LOCKED_MAX_RUNNING, clock).await;
+    Ok(())
+}
 
-init_new_task!(
+async fn limited_task_callback(clock: SharedClock) -> Result<()> {
+    track_concurrency(&LIMITED_RUNNING, &LIMITED_MAX_RUNNING, clock).await;
+    Ok(())
+}
+
+async fn idle_task_callback() -> Result<()> {
+    Ok(())
+}
+
+fn get_retry_policy(max_attempts: u64) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_delay_seconds: 1,
+        max_delay_seconds: 60,
+        jitter_percentage: 0,
+    }
+}
+
+// Settings shared by most of the tasks, which override only what their tests need.
+fn get_config(callback: AsyncFnMut) -> TaskConfig {
     TaskConfig {
-        callback: task_callback!(third_task_callback),
-        run_interval_seconds: 5,
+        callback,
+        schedule: TaskSchedule::Interval(Duration::from_secs(60)),
+        catch_up_policy: CatchUpPolicy::default(),
+        retry_policy: RetryPolicy::default(),
+        timeout_seconds: 0,
+        resource_locks: vec![],
         app_name: constants::ArchiverIdentificator::Tests,
         is_enabled: true,
-    },
-    get_third_task
-);
+    }
+}
+
+fn get_task(config: TaskConfig, clock: &FakeClock) -> Task {
+    Task::with_clock(config, Arc::new(clock.clone()))
+}
+
+fn get_scheduler(tasks: Vec<Task>, clock: &FakeClock) -> schedule::Scheduler {
+    let mut scheduler = schedule::Scheduler::new(Some(tasks));
+    scheduler.set_clock(Arc::new(clock.clone()));
+    scheduler
+}
+
+// Lets spawned tasks handle everything the fake clock has woken them up for. Tests run on a single
+// thread, so yielding is enough for all of them to take their turns.
+async fn settle() {
+    for _ in 0..100 {
+        tokio::task::yield_now().await;
+    }
+}
+
+// Moves the clock a second at a time, letting spawned tasks catch up after each one.
+async fn advance_by_seconds(clock: &FakeClock, seconds: u64) {
+    for _ in 0..seconds {
+        clock.advance(Duration::from_secs(1));
+        settle().await;
+    }
+}
+
+// Runs the task once, moving the clock until it finishes. Returns how long it took.
+async fn run_with_clock(mut task: Task, clock: &FakeClock) -> Duration {
+    let started_at = clock.now();
+    let task_handle = tokio::spawn(async move {
+        task.run().await;
+    });
+    settle().await;
+    while !task_handle.is_finished() {
+        advance_by_seconds(clock, 1).await;
+    }
+
+    clock.now().duration_since(started_at).unwrap()
+}
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+static WEEKLY_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+async fn weekly_task_callback() -> Result<()> {
+    WEEKLY_COUNTER.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+fn get_clocked_task(
+    callback: AsyncFnMut,
+    interval: Duration,
+    catch_up_policy: CatchUpPolicy,
+    clock: &FakeClock,
+) -> Task {
+    Task::with_clock(
+        TaskConfig {
+            callback,
+            schedule: TaskSchedule::Interval(interval),
+            catch_up_policy,
+            retry_policy: RetryPolicy::default(),
+            timeout_seconds: 0,
+            resource_locks: vec![],
+            app_name: constants::ArchiverIdentificator::Tests,
+            is_enabled: true,
+        },
+        Arc::new(clock.clone()),
+    )
+}
+
+static BACKING_OFF_COUNTER: AtomicU32 = AtomicU32::new(0);
+static LOCK_SHARING_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+async fn backing_off_task_callback() -> Result<()> {
+    BACKING_OFF_COUNTER.fetch_add(1, Ordering::SeqCst);
+    bail!("Backing off task failed!");
+}
+
+async fn lock_sharing_task_callback() -> Result<()> {
+    LOCK_SHARING_COUNTER.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+fn get_locking_task(
+    callback: AsyncFnMut,
+    schedule: TaskSchedule,
+    retry_policy: RetryPolicy,
+    clock: &FakeClock,
+) -> Task {
+    Task::with_clock(
+        TaskConfig {
+            callback,
+            schedule,
+            catch_up_policy: CatchUpPolicy::default(),
+            retry_policy,
+            timeout_seconds: 0,
+            resource_locks: vec!["tests"],
+            app_name: constants::ArchiverIdentificator::Tests,
+            is_enabled: true,
+        },
+        Arc::new(clock.clone()),
+    )
+}
+
+static PAUSED_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+async fn paused_task_callback() -> Result<()> {
+    PAUSED_COUNTER.fetch_add(1, Ordering::SeqCst);
+    bail!("Paused task failed!");
+}
+
+static RELOADED_STARTED_COUNTER: AtomicU32 = AtomicU32::new(0);
+static RELOADED_FINISHED_COUNTER: AtomicU32 = AtomicU32::new(0);
+static RELOADING_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+// Takes a while, so that it's still running when the Scheduler is reloaded.
+async fn reloaded_task_callback(clock: SharedClock) -> Result<()> {
+    RELOADED_STARTED_COUNTER.fetch_add(1, Ordering::SeqCst);
+    clock.sleep(Duration::from_millis(500)).await;
+    RELOADED_FINISHED_COUNTER.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+async fn reloading_task_callback() -> Result<()> {
+    RELOADING_COUNTER.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+// Uses an app name of its own, so that its schedule isn't shared with other tests.
+fn get_reloaded_task(callback: AsyncFnMut, clock: &FakeClock) -> Task {
+    Task::with_clock(
+        TaskConfig {
+            callback,
+            schedule: TaskSchedule::Interval(DAY),
+            catch_up_policy: CatchUpPolicy::default(),
+            retry_policy: RetryPolicy::default(),
+            timeout_seconds: 0,
+            resource_locks: vec![],
+            app_name: constants::ArchiverIdentificator::Letterboxd(Some(
+                "reload-tests".to_string(),
+            )),
+            is_enabled: true,
+        },
+        Arc::new(clock.clone()),
+    )
+}
+
+// Simulates the Scheduler coming back after `downtime` of a daily task, which was due right before
+// it went down. Returns how many times the task runs until it catches up, and when its next run is.
+async fn catch_up_after_downtime(policy: CatchUpPolicy, downtime: Duration) -> (u32, SystemTime) {
+    let clock = FakeClock::default();
+    let mut task = get_clocked_task(task_callback!(idle_task_callback), DAY, policy, &clock);
+    clock.advance(downtime);
+
+    let mut amount_of_runs = 0;
+    while task.get_time_until_next_run().is_zero() {
+        if task.skip_missed_runs() {
+            continue;
+        }
+        task.run().await;
+        amount_of_runs += 1;
+        // Each run takes a moment.
+        clock.advance(Duration::from_secs(1));
+    }
+
+    (amount_of_runs, task.get_next_run())
+}
 
 #[cfg(test)]
 mod tests {
     mod scheduler {
-        use std::time::UNIX_EPOCH;
-
         use super::super::*;
+        use asmda::api_wrappers::database::DatabaseClient;
 
         #[tokio::test]
         async fn schedules_tasks() {
-            let tasks = vec![get_first_task(), get_second_task()];
-            let mut scheduler = schedule::Scheduler::new(Some(tasks));
+            let clock = FakeClock::default();
+            let tasks = vec![
+                get_task(
+                    TaskConfig {
+                        schedule: TaskSchedule::Interval(Duration::from_secs(3)),
+                        ..get_config(task_callback!(first_task_callback))
+                    },
+                    &clock,
+                ),
+                get_task(
+                    TaskConfig {
+                        schedule: TaskSchedule::Interval(Duration::from_secs(4)),
+                        ..get_config(task_callback!(second_task_callback))
+                    },
+                    &clock,
+                ),
+            ];
+            let mut scheduler = get_scheduler(tasks, &clock);
 
             let scheduler_handle = tokio::spawn(async move {
                 scheduler.run().await;
             });
-
-            sleep(Duration::from_secs(10)).await;
-
+            settle().await;
+            advance_by_seconds(&clock, 10).await;
             scheduler_handle.abort();
 
             // In 10 seconds:
@@ -90,49 +326,416 @@ mod tests {
             // - a task executing every 4 seconds should run 3 times.
             // The number of executions is increased by one because we divide execute it instantly
             // in the first iteration.
-            let first_counter_value = FIRST_COUNTER.lock().unwrap().clone();
-            let second_counter_value = SECOND_COUNTER.lock().unwrap().clone();
+            let first_counter_value = *FIRST_COUNTER.lock().unwrap();
+            let second_counter_value = *SECOND_COUNTER.lock().unwrap();
             assert_eq!(first_counter_value, 4);
             assert_eq!(second_counter_value, 3);
         }
 
         #[tokio::test]
         async fn remembers_tasks_scheduled_execution_date_across_restarts() {
-            let tasks = vec![get_third_task()];
-            let scheduler = Arc::new(TokioMutex::new(schedule::Scheduler::new(Some(tasks))));
-            let start_timestamp = SystemTime::now();
+            let clock = FakeClock::default();
+            // Uses an app name of its own, so that its schedule isn't shared with other tests.
+            let app_name =
+                constants::ArchiverIdentificator::Letterboxd(Some("restart-tests".to_string()));
+            DatabaseClient::new().update_next_run(app_name.clone(), clock.now());
+            let get_third_task = || {
+                get_task(
+                    TaskConfig {
+                        schedule: TaskSchedule::Interval(Duration::from_secs(5)),
+                        app_name: app_name.clone(),
+                        ..get_config(task_callback!(third_task_callback, clock))
+                    },
+                    &clock,
+                )
+            };
+            let start_timestamp = clock.now();
 
             // Start the Scheduler for 2 seconds.
-            let scheduler_clone = scheduler.clone();
+            let mut scheduler = get_scheduler(vec![get_third_task()], &clock);
             let scheduler_handle = tokio::spawn(async move {
-                let mut unwrapped_scheduler = scheduler_clone.lock().await;
-                unwrapped_scheduler.run().await;
+                scheduler.run().await;
             });
-            sleep(Duration::from_secs(2)).await;
+            settle().await;
+            advance_by_seconds(&clock, 2).await;
             scheduler_handle.abort();
 
-            // Let it run again, this time for 7 seconds.
+            // Let a new one run, this time for 7 seconds.
+            let mut scheduler = get_scheduler(vec![get_third_task()], &clock);
             let scheduler_handle = tokio::spawn(async move {
-                let mut unwrapped_scheduler = scheduler.lock().await;
-                unwrapped_scheduler.run().await;
+                scheduler.run().await;
             });
-            sleep(Duration::from_secs(7)).await;
+            settle().await;
+            advance_by_seconds(&clock, 7).await;
             scheduler_handle.abort();
 
             // Our task was meant to be executed 5 seconds after the `start_timestamp`, no matter
-            // whether the process restarted or not. Verify that latest execution time is equal to
-            // `start_timestamp` + 5 seconds.
-            let latest_execution_timestamp = THIRD_TIMER.lock().unwrap().clone();
-            let latest_timestamps_seconds = latest_execution_timestamp
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            let adjusted_start_timestamp = start_timestamp + Duration::from_secs(5);
-            let adjusted_start_timestamp_seconds = adjusted_start_timestamp
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            assert_eq!(latest_timestamps_seconds, adjusted_start_timestamp_seconds,);
+            // whether the process restarted or not.
+            let latest_execution_timestamp = *THIRD_TIMER.lock().unwrap();
+            assert_eq!(
+                latest_execution_timestamp,
+                start_timestamp + Duration::from_secs(5)
+            );
+        }
+
+        #[tokio::test]
+        async fn retries_failed_runs_with_backoff() {
+            let clock = FakeClock::default();
+            let task = get_task(
+                TaskConfig {
+                    retry_policy: get_retry_policy(3),
+                    ..get_config(task_callback!(flaky_task_callback))
+                },
+                &clock,
+            );
+
+            let elapsed = run_with_clock(task, &clock).await;
+
+            // Attempts are delayed by 1 and then 2 seconds.
+            assert_eq!(*FLAKY_COUNTER.lock().unwrap(), 3);
+            assert_eq!(elapsed, Duration::from_secs(3));
+        }
+
+        #[tokio::test]
+        async fn gives_up_after_max_attempts() {
+            let clock = FakeClock::default();
+            let task = get_task(
+                TaskConfig {
+                    retry_policy: get_retry_policy(2),
+                    ..get_config(task_callback!(failing_task_callback))
+                },
+                &clock,
+            );
+
+            run_with_clock(task, &clock).await;
+
+            assert_eq!(*FAILING_COUNTER.lock().unwrap(), 2);
+        }
+
+        #[tokio::test]
+        async fn cancels_runs_exceeding_timeout() {
+            let clock = FakeClock::default();
+            let task = get_task(
+                TaskConfig {
+                    timeout_seconds: 1,
+                    ..get_config(task_callback!(hanging_task_callback, clock))
+                },
+                &clock,
+            );
+
+            let elapsed = run_with_clock(task, &clock).await;
+
+            assert_eq!(elapsed, Duration::from_secs(1));
+            assert_eq!(*HANGING_COUNTER.lock().unwrap(), 0);
+        }
+
+        #[tokio::test]
+        async fn runs_tasks_sharing_a_resource_one_by_one() {
+            let clock = FakeClock::default();
+            let get_locked_task = || {
+                get_task(
+                    TaskConfig {
+                        resource_locks: vec!["tests"],
+                        ..get_config(task_callback!(locked_task_callback, clock))
+                    },
+                    &clock,
+                )
+            };
+            let mut scheduler = get_scheduler(vec![get_locked_task(), get_locked_task()], &clock);
+            scheduler.set_concurrency_limit(0);
+
+            let scheduler_handle = tokio::spawn(async move {
+                scheduler.run().await;
+            });
+            settle().await;
+            advance_by_seconds(&clock, 5).await;
+            scheduler_handle.abort();
+
+            assert_eq!(LOCKED_MAX_RUNNING.load(Ordering::SeqCst), 1);
+            assert_eq!(LOCKED_RUNNING.load(Ordering::SeqCst), 0);
+        }
+
+        #[tokio::test]
+        async fn respects_concurrency_limit() {
+            let clock = FakeClock::default();
+            let get_limited_task = || {
+                get_task(
+                    get_config(task_callback!(limited_task_callback, clock)),
+                    &clock,
+                )
+            };
+            let tasks = vec![get_limited_task(), get_limited_task(), get_limited_task()];
+            let mut scheduler = get_scheduler(tasks, &clock);
+            scheduler.set_concurrency_limit(2);
+
+            let scheduler_handle = tokio::spawn(async move {
+                scheduler.run().await;
+            });
+            settle().await;
+            advance_by_seconds(&clock, 5).await;
+            scheduler_handle.abort();
+
+            assert_eq!(LIMITED_MAX_RUNNING.load(Ordering::SeqCst), 2);
+            assert_eq!(LIMITED_RUNNING.load(Ordering::SeqCst), 0);
+        }
+
+        #[tokio::test]
+        async fn runs_now_without_shifting_schedule_unless_asked() {
+            let clock = FakeClock::default();
+            let mut task = get_task(get_config(task_callback!(idle_task_callback)), &clock);
+            task.run().await;
+            let scheduled_next_run = task.get_next_run();
+
+            task.run_now(false).await;
+            assert_eq!(task.get_next_run(), scheduled_next_run);
+
+            clock.advance(Duration::from_secs(1));
+            task.run_now(true).await;
+            assert!(task.get_next_run() > scheduled_next_run);
+        }
+    }
+
+    mod clock {
+        use super::super::*;
+        use asmda::api_wrappers::database::DatabaseClient;
+        use asmda::schedule::tasks::utils::retry::RetryState;
+        use asmda::schedule::tasks::utils::types::TaskStatus;
+
+        #[tokio::test]
+        async fn runs_weekly_tasks_without_waiting_for_weeks() {
+            let clock = FakeClock::default();
+            let tasks = vec![get_clocked_task(
+                task_callback!(weekly_task_callback),
+                WEEK,
+                CatchUpPolicy::default(),
+                &clock,
+            )];
+            let mut scheduler = schedule::Scheduler::new(Some(tasks));
+            scheduler.set_clock(Arc::new(clock.clone()));
+
+            let scheduler_handle = tokio::spawn(async move {
+                scheduler.run().await;
+            });
+            settle().await;
+            // Three weeks, a day at a time.
+            for _ in 0..21 {
+                clock.advance(DAY);
+                settle().await;
+            }
+            scheduler_handle.abort();
+
+            // Once right away, and then once a week.
+            assert_eq!(WEEKLY_COUNTER.load(Ordering::SeqCst), 4);
+        }
+
+        #[tokio::test]
+        async fn releases_resources_while_waiting_to_retry() {
+            let clock = FakeClock::default();
+            let retry_policy = RetryPolicy {
+                max_attempts: 2,
+                initial_delay_seconds: 60,
+                max_delay_seconds: 60,
+                jitter_percentage: 0,
+            };
+            let tasks = vec![
+                get_locking_task(
+                    task_callback!(backing_off_task_callback),
+                    TaskSchedule::Interval(DAY),
+                    retry_policy,
+                    &clock,
+                ),
+                // Every second, so it's first due right after the other one has failed.
+                get_locking_task(
+                    task_callback!(lock_sharing_task_callback),
+                    TaskSchedule::parse("* * * * * *", "UTC").unwrap(),
+                    RetryPolicy::default(),
+                    &clock,
+                ),
+            ];
+            let mut scheduler = schedule::Scheduler::new(Some(tasks));
+            scheduler.set_clock(Arc::new(clock.clone()));
+
+            let scheduler_handle = tokio::spawn(async move {
+                scheduler.run().await;
+            });
+            settle().await;
+            assert_eq!(BACKING_OFF_COUNTER.load(Ordering::SeqCst), 1);
+
+            clock.advance(Duration::from_secs(1));
+            settle().await;
+            assert_eq!(LOCK_SHARING_COUNTER.load(Ordering::SeqCst), 1);
+            assert_eq!(BACKING_OFF_COUNTER.load(Ordering::SeqCst), 1);
+
+            clock.advance(Duration::from_secs(60));
+            settle().await;
+            scheduler_handle.abort();
+
+            assert_eq!(BACKING_OFF_COUNTER.load(Ordering::SeqCst), 2);
+        }
+
+        #[tokio::test]
+        async fn stops_retrying_once_paused() {
+            let clock = FakeClock::default();
+            // Uses an app name of its own, so that pausing it doesn't affect other tests.
+            let app_name =
+                constants::ArchiverIdentificator::Letterboxd(Some("pause-tests".to_string()));
+            let mut task = Task::with_clock(
+                TaskConfig {
+                    callback: task_callback!(paused_task_callback),
+                    schedule: TaskSchedule::Interval(DAY),
+                    catch_up_policy: CatchUpPolicy::default(),
+                    retry_policy: get_retry_policy(3),
+                    timeout_seconds: 0,
+                    resource_locks: vec![],
+                    app_name: app_name.clone(),
+                    is_enabled: true,
+                },
+                Arc::new(clock.clone()),
+            );
+
+            let task_handle = tokio::spawn(async move {
+                task.run().await;
+                task
+            });
+            settle().await;
+            assert_eq!(PAUSED_COUNTER.load(Ordering::SeqCst), 1);
+
+            let database = DatabaseClient::new();
+            database.set_task_paused(&app_name.as_str(), true);
+            clock.advance(Duration::from_secs(1));
+            let task = task_handle.await.unwrap();
+            let status = task.get_status();
+            database.set_task_paused(&app_name.as_str(), false);
+
+            assert_eq!(PAUSED_COUNTER.load(Ordering::SeqCst), 1);
+            assert_eq!(status, TaskStatus::Paused);
+        }
+
+        #[test]
+        fn counts_down_to_the_next_attempt_with_the_clock() {
+            let clock = FakeClock::default();
+            let status = TaskStatus::Retrying(RetryState {
+                failed_attempts: 1,
+                max_attempts: 3,
+                next_attempt: clock.now() + Duration::from_secs(60),
+            });
+
+            clock.advance(Duration::from_secs(20));
+
+            assert_eq!(status.as_str(&clock), "Retrying in 40s (attempt 2/3)");
+        }
+
+        #[tokio::test]
+        async fn computes_time_until_next_run_from_the_clock() {
+            let clock = FakeClock::default();
+            let mut task = get_clocked_task(
+                task_callback!(idle_task_callback),
+                WEEK,
+                CatchUpPolicy::default(),
+                &clock,
+            );
+            task.run().await;
+            assert_eq!(task.get_next_run(), clock.now() + WEEK);
+
+            clock.advance(DAY);
+            assert_eq!(task.get_time_until_next_run(), WEEK - DAY);
+        }
+    }
+
+    mod reload {
+        use super::super::*;
+        use asmda::api_wrappers::database::DatabaseClient;
+
+        #[tokio::test]
+        async fn replaces_tasks_once_runs_in_progress_finish() {
+            let clock = FakeClock::default();
+            let app_name =
+                constants::ArchiverIdentificator::Letterboxd(Some("reload-tests".to_string()));
+            DatabaseClient::new().update_next_run(app_name, clock.now());
+
+            let tasks = vec![get_reloaded_task(
+                task_callback!(reloaded_task_callback, clock),
+                &clock,
+            )];
+            let mut scheduler = schedule::Scheduler::new(Some(tasks));
+            scheduler.set_clock(Arc::new(clock.clone()));
+            scheduler.run().await;
+            settle().await;
+            assert_eq!(RELOADED_STARTED_COUNTER.load(Ordering::SeqCst), 1);
+
+            let tasks = vec![get_reloaded_task(
+                task_callback!(reloading_task_callback),
+                &clock,
+            )];
+            scheduler.reload(tasks).await;
+            settle().await;
+            // The run in progress isn't cancelled.
+            assert_eq!(RELOADED_FINISHED_COUNTER.load(Ordering::SeqCst), 0);
+
+            clock.advance(Duration::from_millis(500));
+            settle().await;
+            assert_eq!(RELOADED_FINISHED_COUNTER.load(Ordering::SeqCst), 1);
+
+            clock.advance(DAY);
+            settle().await;
+
+            // Only the new task runs on the following day.
+            assert_eq!(RELOADED_STARTED_COUNTER.load(Ordering::SeqCst), 1);
+            assert_eq!(RELOADING_COUNTER.load(Ordering::SeqCst), 1);
+        }
+    }
+
+    mod catch_up {
+        use super::super::*;
+
+        const MONTH: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+        #[tokio::test]
+        async fn runs_once_and_realigns_to_now() {
+            let (amount_of_runs, next_run) =
+                catch_up_after_downtime(CatchUpPolicy::RunOnce, MONTH).await;
+
+            let expected_next_run = FakeClock::default().now() + MONTH + DAY;
+            assert_eq!(amount_of_runs, 1);
+            assert_eq!(next_run, expected_next_run);
+        }
+
+        #[tokio::test]
+        async fn skips_missed_runs() {
+            let (amount_of_runs, next_run) =
+                catch_up_after_downtime(CatchUpPolicy::Skip, MONTH).await;
+
+            let expected_next_run = FakeClock::default().now() + MONTH + DAY;
+            assert_eq!(amount_of_runs, 0);
+            assert_eq!(next_run, expected_next_run);
+        }
+
+        #[tokio::test]
+        async fn runs_all_missed_runs() {
+            let (amount_of_runs, next_run) =
+                catch_up_after_downtime(CatchUpPolicy::RunAll, MONTH).await;
+
+            // Every day of the month, plus the one due right before the downtime. The schedule
+            // stays aligned to the original dates.
+            let expected_next_run = FakeClock::default().now() + MONTH + DAY;
+            assert_eq!(amount_of_runs, 30 + 1);
+            assert_eq!(next_run, expected_next_run);
+        }
+
+        #[tokio::test]
+        async fn runs_late_runs_regardless_of_policy() {
+            // Less than one interval late, so nothing has been missed.
+            for policy in [
+                CatchUpPolicy::RunOnce,
+                CatchUpPolicy::Skip,
+                CatchUpPolicy::RunAll,
+            ] {
+                let (amount_of_runs, next_run) =
+                    catch_up_after_downtime(policy, Duration::from_secs(60)).await;
+
+                assert_eq!(amount_of_runs, 1);
+                assert_eq!(next_run, FakeClock::default().now() + DAY);
+            }
         }
     }
 }